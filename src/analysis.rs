@@ -0,0 +1,240 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+	dijkstra_map::DijkstraMap,
+	generation::{GenerationError, GenerationStats},
+	geometry::{TilePoint, TileRectangle, NEIGHBOR_OFFSETS_FOUR},
+	level::Tile,
+};
+
+/// Connectivity and playability measurements of a generated level.
+#[derive(Debug)]
+pub struct LevelAnalysis {
+	/// The number of separate four-connected regions of open floor.
+	pub components: usize,
+	/// The number of open tiles that can't be reached from the start.
+	pub unreachable_tiles: usize,
+	/// The proportion of the level's interior (the tileport minus its outer
+	/// border, counted as at least one tile) that is open floor.
+	pub floor_ratio: f32,
+	/// The number of open tiles with exactly one open neighbor.
+	pub dead_ends: usize,
+	/// An estimate of the length of the longest shortest path between two
+	/// tiles reachable from the start, found by walking from the start to the
+	/// farthest tile and then to the tile farthest from that. This is exact
+	/// when the level has no loops, but otherwise it may fall short of the true
+	/// length, so it should be treated as a lower bound.
+	pub longest_path_estimate: isize,
+}
+
+/// Thresholds a generated level must meet to be accepted.
+#[derive(Clone)]
+pub struct LevelRequirements {
+	/// Whether every open tile must be reachable from the start.
+	pub connected: bool,
+	/// The minimum acceptable [`LevelAnalysis::floor_ratio`].
+	pub min_floor_ratio: f32,
+	/// The maximum acceptable [`LevelAnalysis::dead_ends`].
+	pub max_dead_ends: usize,
+	/// The minimum acceptable [`LevelAnalysis::longest_path_estimate`].
+	pub min_longest_path_estimate: isize,
+}
+
+impl LevelAnalysis {
+	/// Analyzes `terrain` covering `tileport`, with the player starting at
+	/// `start`.
+	pub fn new(
+		terrain: &HashMap<TilePoint, Tile>,
+		tileport: TileRectangle,
+		start: TilePoint,
+	) -> LevelAnalysis {
+		let open = terrain
+			.iter()
+			.filter(|(_, tile)| matches!(tile, Tile::Floor(_)))
+			.map(|(coords, _)| *coords)
+			.collect::<HashSet<_>>();
+		let walk_map = |goal: TilePoint| {
+			DijkstraMap::new(
+				open.iter().copied(),
+				|coords| *coords == goal,
+				|coords| !open.contains(coords),
+			)
+		};
+
+		// Flood fill from an unvisited tile until every tile is visited.
+		let mut unvisited = open.clone();
+		let mut components = 0;
+		while let Some(&coords) = unvisited.iter().next() {
+			for (coords, _) in walk_map(coords).reachable() {
+				unvisited.remove(&coords);
+			}
+			components += 1;
+		}
+
+		let from_start = walk_map(start);
+		// Break ties by position so that the estimate doesn't depend on
+		// iteration order.
+		let farthest = |map: &DijkstraMap| {
+			map.reachable().max_by_key(|(coords, distance)| {
+				(*distance, coords.y, coords.x)
+			})
+		};
+		let longest_path_estimate = farthest(&from_start)
+			.and_then(|(coords, _)| farthest(&walk_map(coords)))
+			.map_or(0, |(_, distance)| distance);
+
+		// Levels under 3x3 have no interior, so avoid dividing by zero, or by a
+		// negative area.
+		let interior = tileport.expanded(-1).size;
+		let interior_area = (interior.x.max(0) * interior.y.max(0)).max(1);
+
+		LevelAnalysis {
+			components,
+			unreachable_tiles: open.len() - from_start.reachable().count(),
+			floor_ratio: open.len() as f32 / interior_area as f32,
+			dead_ends: open
+				.iter()
+				.filter(|coords| {
+					NEIGHBOR_OFFSETS_FOUR
+						.iter()
+						.filter(|offset| open.contains(&(**coords + **offset)))
+						.count() == 1
+				})
+				.count(),
+			longest_path_estimate,
+		}
+	}
+
+	/// Checks the analysis against `requirements`, returning the first failed
+	/// requirement as an error. The generator's `stats` help explain failures.
+	pub fn check(
+		&self,
+		requirements: &LevelRequirements,
+		stats: &GenerationStats,
+	) -> Result<(), GenerationError> {
+		if requirements.connected && self.unreachable_tiles > 0 {
+			return Err(GenerationError::Disconnected {
+				components: self.components,
+				failed_routes: stats.failed_routes,
+			});
+		}
+		if self.floor_ratio < requirements.min_floor_ratio {
+			return Err(GenerationError::FloorRatioTooLow {
+				ratio: self.floor_ratio,
+				min: requirements.min_floor_ratio,
+			});
+		}
+		if self.dead_ends > requirements.max_dead_ends {
+			return Err(GenerationError::TooManyDeadEnds {
+				count: self.dead_ends,
+				max: requirements.max_dead_ends,
+			});
+		}
+		if self.longest_path_estimate < requirements.min_longest_path_estimate {
+			return Err(GenerationError::PathTooShort {
+				length: self.longest_path_estimate,
+				min: requirements.min_longest_path_estimate,
+			});
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{geometry::TileVector, level::Floor};
+
+	/// Analyzes a rectangular `map` of walls (`#`) and floors (`.`), starting
+	/// from `@`.
+	fn analyze(map: &str) -> LevelAnalysis {
+		let mut terrain = HashMap::new();
+		let mut start = TilePoint::new(0, 0);
+		for (y, line) in map.lines().enumerate() {
+			for (x, character) in line.chars().enumerate() {
+				let coords = TilePoint::new(x as i32, y as i32);
+				if character == '@' {
+					start = coords;
+				}
+				let tile = match character {
+					'#' => Tile::Wall,
+					_ => Tile::Floor(Floor::Stone),
+				};
+				terrain.insert(coords, tile);
+			}
+		}
+		let width = map.lines().next().map_or(0, str::len);
+		let tileport = TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size: TileVector::new(width as i32, map.lines().count() as i32),
+		};
+		LevelAnalysis::new(&terrain, tileport, start)
+	}
+
+	fn lenient() -> LevelRequirements {
+		LevelRequirements {
+			connected: true,
+			min_floor_ratio: 0.0,
+			max_dead_ends: 10,
+			min_longest_path_estimate: 0,
+		}
+	}
+
+	#[test]
+	fn measures_disconnected_rooms() {
+		let analysis = analyze(
+			"#########\n\
+			 #@.#....#\n\
+			 #..#....#\n\
+			 #########",
+		);
+		assert_eq!(analysis.components, 2);
+		assert_eq!(analysis.unreachable_tiles, 8);
+		assert_eq!(analysis.dead_ends, 0);
+		assert_eq!(analysis.floor_ratio, 12.0 / 14.0);
+		assert!(matches!(
+			analysis.check(&lenient(), &GenerationStats::default()),
+			Err(GenerationError::Disconnected { components: 2, .. })
+		));
+		let requirements = LevelRequirements {
+			connected: false,
+			..lenient()
+		};
+		let stats = GenerationStats::default();
+		assert!(analysis.check(&requirements, &stats).is_ok());
+	}
+
+	#[test]
+	fn measures_a_dead_end_corridor() {
+		let analysis = analyze(
+			"########\n\
+			 #@.....#\n\
+			 #..#####\n\
+			 ########",
+		);
+		assert_eq!(analysis.components, 1);
+		assert_eq!(analysis.unreachable_tiles, 0);
+		assert_eq!(analysis.dead_ends, 1);
+		// From the end of the corridor to the far corner of the room.
+		assert_eq!(analysis.longest_path_estimate, 6);
+
+		let stats = GenerationStats::default();
+		assert!(analysis.check(&lenient(), &stats).is_ok());
+		let requirements = LevelRequirements {
+			max_dead_ends: 0,
+			..lenient()
+		};
+		assert!(matches!(
+			analysis.check(&requirements, &stats),
+			Err(GenerationError::TooManyDeadEnds { count: 1, max: 0 })
+		));
+		let requirements = LevelRequirements {
+			min_longest_path_estimate: 7,
+			..lenient()
+		};
+		assert!(matches!(
+			analysis.check(&requirements, &stats),
+			Err(GenerationError::PathTooShort { length: 6, min: 7 })
+		));
+	}
+}
@@ -0,0 +1,146 @@
+use std::{f32::consts::TAU, rc::Rc, time::Duration};
+
+use ggez::graphics::Color;
+
+use crate::{
+	creature::{Creature, Species},
+	geometry::{ScreenVector, TilePoint},
+	level::Level,
+	renderer::Pose,
+	shared::Shared,
+};
+
+/// How long a creature takes to slide from one tile to the next.
+const MOVE_DURATION: Duration = Duration::from_millis(60);
+
+/// How long a creature shakes and flashes after being hit.
+const HIT_DURATION: Duration = Duration::from_millis(250);
+
+/// How long a creature takes to fade away after dying.
+const DEATH_DURATION: Duration = Duration::from_millis(400);
+
+/// How far a hit creature shakes to either side, in tiles.
+const SHAKE_AMPLITUDE: f32 = 0.1;
+
+/// How many times per second a hit creature shakes back and forth.
+const SHAKES_PER_SECOND: f32 = 16.0;
+
+/// The creatures in a level and their positions and health, taken before a
+/// turn to compare against afterwards.
+pub struct Snapshot(Vec<(Shared<Creature>, TilePoint, u32)>);
+
+impl Snapshot {
+	pub fn new(level: &Level) -> Snapshot {
+		Snapshot(
+			level
+				.creatures()
+				.map(|shared| {
+					let creature = shared.borrow();
+					(shared.clone(), creature.coords, creature.stats.health())
+				})
+				.collect(),
+		)
+	}
+}
+
+/// Plays out what happened during the most recent turn: creatures slide
+/// between tiles, shake and flash when hit, and fade away when they die.
+/// Animations only affect how creatures are drawn. The game stays turn-based,
+/// and starting the next turn skips whatever is left of the last one's
+/// animations.
+#[derive(Default)]
+pub struct Animations {
+	/// Time since the most recent turn.
+	elapsed: Duration,
+	/// Creatures that moved, and the tiles they moved from.
+	moves: Vec<(Shared<Creature>, TilePoint)>,
+	/// Creatures that were hit and survived.
+	hits: Vec<Shared<Creature>>,
+	/// The species and last position of each creature that died.
+	deaths: Vec<(Species, TilePoint)>,
+}
+
+impl Animations {
+	/// Starts animating the changes to creatures since `before` was taken.
+	pub fn start(&mut self, before: Snapshot) {
+		*self = Animations::default();
+		for (shared, coords, health) in before.0 {
+			let creature = shared.borrow();
+			if creature.dead() {
+				self.deaths.push((creature.species, creature.coords));
+				continue;
+			}
+			if creature.stats.health() < health {
+				self.hits.push(shared.clone());
+			}
+			if creature.coords != coords {
+				self.moves.push((shared.clone(), coords));
+			}
+		}
+	}
+
+	/// Moves the animations forward by `dt`.
+	pub fn advance(&mut self, dt: Duration) {
+		self.elapsed += dt;
+	}
+
+	/// Whether any animation is still playing.
+	pub fn playing(&self) -> bool {
+		(!self.moves.is_empty() && self.elapsed < MOVE_DURATION)
+			|| (!self.hits.is_empty() && self.elapsed < HIT_DURATION)
+			|| (!self.deaths.is_empty() && self.elapsed < DEATH_DURATION)
+	}
+
+	/// How to show `creature` at this point in the animations.
+	pub fn pose(&self, creature: &Shared<Creature>) -> Pose {
+		let mut pose = Pose::STILL;
+		if let Some((_, from)) = self
+			.moves
+			.iter()
+			.find(|(moved, _)| Rc::ptr_eq(moved, creature))
+		{
+			// Ease in and out of the slide.
+			let t = self.progress(MOVE_DURATION);
+			let remaining = 1.0 - t * t * (3.0 - 2.0 * t);
+			let offset = *from - creature.borrow().coords;
+			pose.offset = ScreenVector::new(
+				remaining * offset.x as f32,
+				remaining * offset.y as f32,
+			);
+		}
+		if self.elapsed < HIT_DURATION
+			&& self.hits.iter().any(|hit| Rc::ptr_eq(hit, creature))
+		{
+			let phase =
+				(self.elapsed.as_secs_f32() * SHAKES_PER_SECOND * TAU).sin();
+			pose.offset.x +=
+				SHAKE_AMPLITUDE * phase * (1.0 - self.progress(HIT_DURATION));
+			// Flash on every other half shake.
+			if phase < 0.0 {
+				pose.color.a = 0.3;
+			}
+		}
+		pose
+	}
+
+	/// The species, last position, and pose of each creature still fading away
+	/// after dying.
+	pub fn dying(
+		&self,
+	) -> impl Iterator<Item = (Species, TilePoint, Pose)> + '_ {
+		let alpha = 1.0 - self.progress(DEATH_DURATION);
+		let pose = Pose {
+			color: Color::new(1.0, 1.0, 1.0, alpha),
+			..Pose::STILL
+		};
+		self.deaths
+			.iter()
+			.filter(move |_| alpha > 0.0)
+			.map(move |&(species, coords)| (species, coords, pose))
+	}
+
+	/// The fraction of `duration` that has passed since the turn, up to 1.
+	fn progress(&self, duration: Duration) -> f32 {
+		(self.elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+	}
+}
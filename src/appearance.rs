@@ -0,0 +1,54 @@
+use ggez::graphics::Color;
+
+use crate::{
+	creature::Species,
+	item::ItemKind,
+	level::{Floor, Tile},
+};
+
+/// How something looks, shared by every way of presenting the game: the
+/// character standing for it in text and glyph maps, and its color wherever
+/// color is shown.
+#[derive(Clone, Copy)]
+pub struct Appearance {
+	pub glyph: char,
+	pub color: Color,
+}
+
+impl Tile {
+	/// How this tile looks.
+	pub fn appearance(&self) -> Appearance {
+		let (glyph, color) = match self {
+			Tile::Floor(Floor::Stone) => ('.', Color::from_rgb(128, 128, 128)),
+			Tile::Floor(Floor::Grass) => ('"', Color::from_rgb(35, 80, 25)),
+			Tile::Floor(Floor::Wood) => ('=', Color::from_rgb(96, 58, 32)),
+			Tile::Wall => ('#', Color::from_rgb(128, 0, 0)),
+		};
+		Appearance { glyph, color }
+	}
+}
+
+impl Species {
+	/// How a creature of this species looks.
+	pub fn appearance(&self) -> Appearance {
+		let (glyph, color) = match self {
+			Species::Human => ('@', Color::from_rgb(0, 192, 255)),
+			Species::Goblin => ('g', Color::from_rgb(0, 160, 0)),
+			Species::Ogre => ('O', Color::RED),
+		};
+		Appearance { glyph, color }
+	}
+}
+
+impl ItemKind {
+	/// How an item of this kind looks.
+	pub fn appearance(&self) -> Appearance {
+		let (glyph, color) = match self {
+			ItemKind::Gold => ('$', Color::YELLOW),
+			ItemKind::Potion => ('!', Color::MAGENTA),
+			ItemKind::Scroll => ('?', Color::WHITE),
+			ItemKind::Weapon => (')', Color::from_rgb(192, 192, 208)),
+		};
+		Appearance { glyph, color }
+	}
+}
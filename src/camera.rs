@@ -0,0 +1,171 @@
+use crate::{
+	geometry::{ScreenRectangle, TilePoint, TileRectangle, TileVector},
+	level::TileLayout,
+};
+
+/// Chooses which part of a level to show: a window of tiles centered on a
+/// focus point, clamped to the level's bounds, and sized so that tiles appear
+/// at a preferred size on screen.
+pub struct Camera {
+	/// The region of the screen to show the window in.
+	viewport: ScreenRectangle,
+	/// The tiles the camera may show.
+	bounds: TileRectangle,
+	/// The preferred on-screen width and height of a tile.
+	tile_size: f32,
+	/// The point the window is centered on, where possible.
+	focus: TilePoint,
+	/// The tiles currently shown.
+	window: TileRectangle,
+	tile_layout: TileLayout,
+}
+
+impl Camera {
+	/// A camera showing part of `bounds` in `viewport`, initially centered on
+	/// the middle of `bounds`.
+	pub fn new(
+		viewport: ScreenRectangle,
+		bounds: TileRectangle,
+		tile_size: f32,
+	) -> Camera {
+		let mut camera = Camera {
+			viewport,
+			bounds,
+			tile_size,
+			focus: bounds.pos + bounds.size / 2,
+			window: bounds,
+			tile_layout: TileLayout::new(viewport, bounds),
+		};
+		camera.update();
+		camera
+	}
+
+	/// The tiles the camera may show.
+	pub fn bounds(&self) -> TileRectangle {
+		self.bounds
+	}
+
+	/// The tiles currently shown.
+	pub fn window(&self) -> TileRectangle {
+		self.window
+	}
+
+	/// The mapping from the tiles currently shown to the screen.
+	pub fn tile_layout(&self) -> &TileLayout {
+		&self.tile_layout
+	}
+
+	/// Centers the window on `focus`, as nearly as possible without showing
+	/// anything outside the camera's bounds. If the bounds are smaller than the
+	/// window along some axis, the window shrinks to fit, and the tiles are
+	/// scaled up to fill the viewport.
+	pub fn follow(&mut self, focus: TilePoint) {
+		self.focus = focus;
+		self.update();
+	}
+
+	/// Moves the window to a new region of the screen, resizing it to keep
+	/// tiles near their preferred size.
+	pub fn set_viewport(&mut self, viewport: ScreenRectangle) {
+		self.viewport = viewport;
+		self.update();
+	}
+
+	/// Recomputes the window and tile layout from the viewport and focus.
+	fn update(&mut self) {
+		let fit = |viewport: f32, bounds: i32| {
+			((viewport / self.tile_size) as i32).clamp(1, bounds.max(1))
+		};
+		let size = TileVector::new(
+			fit(self.viewport.size.x, self.bounds.size.x),
+			fit(self.viewport.size.y, self.bounds.size.y),
+		);
+		let clamp = |focus: i32, size: i32, start: i32, length: i32| {
+			(focus - size / 2).clamp(start, start + (length - size).max(0))
+		};
+		let (focus, bounds) = (self.focus, self.bounds);
+		self.window = TileRectangle {
+			pos: TilePoint::new(
+				clamp(focus.x, size.x, bounds.pos.x, bounds.size.x),
+				clamp(focus.y, size.y, bounds.pos.y, bounds.size.y),
+			),
+			size,
+		};
+		self.tile_layout = TileLayout::new(self.viewport, self.window);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::geometry::{ScreenPoint, ScreenVector};
+
+	/// A viewport 20 tiles wide and 10 tiles tall at the preferred tile size.
+	const VIEWPORT: ScreenRectangle = ScreenRectangle {
+		pos: ScreenPoint::new(0.0, 0.0),
+		size: ScreenVector::new(320.0, 160.0),
+	};
+
+	fn camera_over(x: i32, y: i32, width: i32, height: i32) -> Camera {
+		let bounds = TileRectangle {
+			pos: TilePoint::new(x, y),
+			size: TileVector::new(width, height),
+		};
+		Camera::new(VIEWPORT, bounds, 16.0)
+	}
+
+	fn window(x: i32, y: i32, width: i32, height: i32) -> TileRectangle {
+		TileRectangle {
+			pos: TilePoint::new(x, y),
+			size: TileVector::new(width, height),
+		}
+	}
+
+	#[test]
+	fn centers_on_the_focus() {
+		let mut camera = camera_over(0, 0, 64, 36);
+		assert_eq!(camera.window(), window(22, 13, 20, 10));
+		camera.follow(TilePoint::new(30, 20));
+		assert_eq!(camera.window(), window(20, 15, 20, 10));
+	}
+
+	#[test]
+	fn clamps_to_the_bounds() {
+		let mut camera = camera_over(0, 0, 64, 36);
+		camera.follow(TilePoint::new(0, 0));
+		assert_eq!(camera.window(), window(0, 0, 20, 10));
+		camera.follow(TilePoint::new(63, 35));
+		assert_eq!(camera.window(), window(44, 26, 20, 10));
+		camera.follow(TilePoint::new(-100, 100));
+		assert_eq!(camera.window(), window(0, 26, 20, 10));
+	}
+
+	#[test]
+	fn fits_small_levels() {
+		// Narrower than the window, but taller.
+		let mut camera = camera_over(5, 5, 8, 30);
+		camera.follow(TilePoint::new(0, 0));
+		assert_eq!(camera.window(), window(5, 5, 8, 10));
+		camera.follow(TilePoint::new(9, 34));
+		assert_eq!(camera.window(), window(5, 25, 8, 10));
+
+		// Smaller than the window in both directions, so the tiles are scaled
+		// up to fill the viewport.
+		let camera = camera_over(0, 0, 8, 4);
+		assert_eq!(camera.window(), window(0, 0, 8, 4));
+		assert_eq!(
+			camera.tile_layout().tile_size(),
+			ScreenVector::new(40.0, 40.0)
+		);
+	}
+
+	#[test]
+	fn resizes_with_the_viewport() {
+		let mut camera = camera_over(0, 0, 64, 36);
+		camera.set_viewport(ScreenRectangle {
+			pos: ScreenPoint::new(0.0, 0.0),
+			size: ScreenVector::new(160.0, 160.0),
+		});
+		assert_eq!(camera.window(), window(27, 13, 10, 10));
+	}
+}
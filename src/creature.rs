@@ -1,14 +1,12 @@
-use ggez::graphics::{Canvas, DrawParam};
 use rand_pcg::Pcg32;
 
 use crate::{
 	geometry::{random_neighbor_offset_four, TilePoint},
-	level::{Level, TileLayout},
-	meshes::Meshes,
+	level::Level,
 };
 
 /// A type of [`Creature`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum Species {
 	Human,
 	Goblin,
@@ -16,6 +14,19 @@ pub enum Species {
 }
 
 impl Species {
+	/// Every species.
+	pub const ALL: [Species; 3] =
+		[Species::Human, Species::Goblin, Species::Ogre];
+
+	/// A short name for a creature of this species, with an article.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Species::Human => "a human",
+			Species::Goblin => "a goblin",
+			Species::Ogre => "an ogre",
+		}
+	}
+
 	fn base_stats(&self) -> Stats {
 		match self {
 			Species::Human => Stats {
@@ -40,10 +51,24 @@ pub enum Faction {
 	Enemy,
 }
 
-#[derive(Debug)]
+impl Faction {
+	/// What a member of this faction is to the player.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Faction::Ally => "ally",
+			Faction::Enemy => "enemy",
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
 pub enum Behavior {
 	Idle,
-	Patrolling,
+	/// Chases nearby enemies, and otherwise walks from room to room.
+	Patrolling {
+		/// The index of the room the creature is walking to, if any.
+		destination: Option<usize>,
+	},
 }
 
 #[derive(Debug)]
@@ -52,6 +77,12 @@ pub struct Stats {
 	pub strength: u32,
 }
 
+impl Stats {
+	pub fn health(&self) -> u32 {
+		self.health
+	}
+}
+
 /// An animate being.
 #[derive(Debug)]
 pub struct Creature {
@@ -78,30 +109,10 @@ impl Creature {
 		}
 	}
 
-	pub fn draw(
-		&self,
-		canvas: &mut Canvas,
-		meshes: &Meshes,
-		layout: &TileLayout,
-	) {
-		let tile_layout = layout.to_screen(self.coords);
-		let mesh = match self.species {
-			Species::Human => &meshes.human,
-			Species::Goblin => &meshes.goblin,
-			Species::Ogre => &meshes.ogre,
-		};
-		canvas.draw(
-			mesh,
-			DrawParam::new()
-				.dest(tile_layout.pos + tile_layout.size / 2.0)
-				.scale(tile_layout.size),
-		);
-	}
-
 	pub fn act(&mut self, level: &mut Level, rng: &mut Pcg32) {
 		match self.behavior {
 			Behavior::Idle => {}
-			Behavior::Patrolling => {
+			Behavior::Patrolling { destination } => {
 				if let Some(map) =
 					level.dijkstra_maps().enemies.get(&self.faction)
 				{
@@ -128,12 +139,42 @@ impl Creature {
 						return;
 					}
 				}
-				// Wander in a random direction.
-				level.translate_creature(self, random_neighbor_offset_four(rng))
+				self.patrol(level, destination, rng);
 			}
 		}
 	}
 
+	/// Takes a step towards the `destination` room, choosing a new destination
+	/// from the level's room graph on arrival. Wanders randomly if there's no
+	/// reachable room.
+	fn patrol(
+		&mut self,
+		level: &mut Level,
+		destination: Option<usize>,
+		rng: &mut Pcg32,
+	) {
+		let maps = &level.dijkstra_maps().rooms;
+		let reachable = |room: &usize| {
+			maps[*room].distance(self.coords).is_some_and(|d| d > 0)
+		};
+		let destination = destination.filter(reachable).or_else(|| {
+			level
+				.room_graph()
+				.next_room(self.coords, rng)
+				.filter(reachable)
+		});
+		self.behavior = Behavior::Patrolling { destination };
+		let step = destination
+			.and_then(|room| maps[room].step_towards(self.coords, rng));
+		let offset = step.unwrap_or_else(|| random_neighbor_offset_four(rng));
+		level.translate_creature(self, offset)
+	}
+
+	/// The health the creature starts with.
+	pub fn max_health(&self) -> u32 {
+		self.species.base_stats().health
+	}
+
 	pub fn take_damage(&mut self, damage: u32) {
 		self.stats.health = self.stats.health.saturating_sub(damage);
 	}
@@ -60,6 +60,13 @@ impl DijkstraMap {
 		self.distances.get(&coords).copied()
 	}
 
+	/// Every tile with a path to a tile of interest, along with its distance.
+	pub fn reachable(&self) -> impl Iterator<Item = (TilePoint, isize)> + '_ {
+		self.distances
+			.iter()
+			.map(|(coords, distance)| (*coords, *distance))
+	}
+
 	/// Offset to a random neighbor of `coords` that is one tile closer to a
 	/// tile of interest, if there is such a neighbor.
 	pub fn step_towards(
@@ -1,19 +1,43 @@
 use ggez::{
-	event,
-	graphics::{Canvas, Color},
+	event::{self, MouseButton},
+	graphics::{Canvas, Color, DrawParam, Text},
 	input::keyboard::{KeyCode, KeyInput},
 	Context, GameResult,
 };
 use rand_pcg::Pcg32;
 
 use crate::{
+	animation::{Animations, Snapshot},
 	creature::Creature,
-	geometry::{TileVector, TILE_DOWN, TILE_LEFT, TILE_RIGHT, TILE_UP},
-	level::Level,
+	geometry::{
+		ScreenPoint, ScreenRectangle, ScreenVector, TilePoint, TileVector,
+		TILE_DOWN, TILE_LEFT, TILE_RIGHT, TILE_UP,
+	},
+	hud,
+	level::{Level, Tile},
 	meshes::Meshes,
+	renderer::{GlyphRenderer, MeshRenderer, Renderer, SpriteRenderer},
 	shared::Shared,
+	tileset::Tileset,
 };
 
+/// How many steps per second the player takes while travelling.
+const TRAVEL_STEPS_PER_SECOND: u32 = 15;
+
+/// Space between the edges of a tooltip and its text.
+const TOOLTIP_PADDING: f32 = 4.0;
+
+/// A way of drawing the level, which the player can switch between.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Presentation {
+	/// Flat colored shapes.
+	Meshes,
+	/// Sprites from the tileset.
+	Sprites,
+	/// Colored characters.
+	Glyphs,
+}
+
 enum Action {
 	Wait,
 	Move { offset: TileVector },
@@ -24,25 +48,218 @@ pub struct GameState {
 	pub player: Shared<Creature>,
 	pub level: Level,
 	pub meshes: Meshes,
+	/// Sprites to draw with, if any.
+	pub tileset: Option<Tileset>,
+	presentation: Presentation,
+	/// Where the mouse cursor is, once it's moved over the window.
+	mouse: Option<ScreenPoint>,
+	/// Where the player is travelling to, if anywhere.
+	destination: Option<TilePoint>,
+	/// The tile the player is examining, while in look mode.
+	look_cursor: Option<TilePoint>,
+	/// Shows what happened during the most recent turn.
+	animations: Animations,
+	/// The description of the room the player is in, shown in the HUD from
+	/// when they enter it until they leave.
+	room_feeling: Option<String>,
 }
 
 impl GameState {
+	pub fn new(
+		rng: Pcg32,
+		player: Shared<Creature>,
+		level: Level,
+		meshes: Meshes,
+		tileset: Option<Tileset>,
+	) -> GameState {
+		// Prefer sprites when there are any.
+		let presentation = match tileset {
+			Some(_) => Presentation::Sprites,
+			None => Presentation::Meshes,
+		};
+		GameState {
+			rng,
+			player,
+			level,
+			meshes,
+			tileset,
+			presentation,
+			mouse: None,
+			destination: None,
+			look_cursor: None,
+			animations: Animations::default(),
+			room_feeling: None,
+		}
+	}
+
+	/// Lays the level out to fill the window's current drawable area, beside
+	/// the HUD.
+	pub fn fit_to_window(&mut self, ctx: &Context) {
+		let screen = screen(ctx);
+		// Keep the old layout while the window has no area, e.g. when it's
+		// minimized.
+		if screen.size.x > 0.0 && screen.size.y > 0.0 {
+			self.level.set_viewport(hud::split(screen).0);
+		}
+	}
+
+	/// Switches to the next way of drawing the level, skipping sprites if
+	/// there's no tileset.
+	fn switch_presentation(&mut self) {
+		self.presentation = match self.presentation {
+			Presentation::Meshes if self.tileset.is_some() => {
+				Presentation::Sprites
+			}
+			Presentation::Meshes | Presentation::Sprites => {
+				Presentation::Glyphs
+			}
+			Presentation::Glyphs => Presentation::Meshes,
+		};
+	}
+
 	fn act(&mut self, action: Action) {
+		let before = Snapshot::new(&self.level);
 		match action {
 			Action::Wait => {}
 			Action::Move { offset } => {
-				self.level
-					.translate_creature(&mut self.player.borrow_mut(), offset);
+				let mut player = self.player.borrow_mut();
+				let room_graph = self.level.room_graph();
+				let old_room = room_graph.room_at(player.coords);
+				self.level.translate_creature(&mut player, offset);
+				// Describe each new room the player enters.
+				let room_graph = self.level.room_graph();
+				let new_room = room_graph.room_at(player.coords);
+				if new_room != old_room {
+					self.room_feeling =
+						new_room.map(|i| room_graph.rooms[i].feeling());
+				}
 			}
 		}
 		self.level.update_dijkstra_maps();
 		self.level.update(&mut self.rng);
 		self.level.update_vision(self.player.borrow().coords);
+		self.level.update_camera(self.player.borrow().coords);
+		self.animations.start(before);
+	}
+
+	/// Takes the player one step towards their travel destination, if any.
+	/// Travel stops on arrival, when the way is blocked, or when an enemy is
+	/// in view.
+	fn travel(&mut self) {
+		let Some(destination) = self.destination else {
+			return;
+		};
+		let start = self.player.borrow().coords;
+		let step = self
+			.level
+			.travel_map(destination)
+			.step_towards(start, &mut self.rng);
+		let Some(offset) = step.filter(|_| !self.enemy_in_view()) else {
+			self.destination = None;
+			return;
+		};
+		self.act(Action::Move { offset });
+		let player = self.player.borrow();
+		if player.coords == start
+			|| player.coords == destination
+			|| player.dead()
+		{
+			self.destination = None;
+		}
+	}
+
+	/// Whether the player can see any creature of another faction.
+	fn enemy_in_view(&self) -> bool {
+		let faction = self.player.borrow().faction;
+		self.level
+			.visible_creatures()
+			.any(|creature| creature.borrow().faction != faction)
+	}
+
+	/// Handles a key press in look mode, moving the cursor within view or
+	/// leaving look mode.
+	fn look(&mut self, cursor: TilePoint, keycode: KeyCode) {
+		let offset = match keycode {
+			KeyCode::Escape | KeyCode::L => {
+				self.look_cursor = None;
+				return;
+			}
+			KeyCode::Up => TILE_UP,
+			KeyCode::Down => TILE_DOWN,
+			KeyCode::Left => TILE_LEFT,
+			KeyCode::Right => TILE_RIGHT,
+			_ => return,
+		};
+		let moved = cursor + offset;
+		if self.level.tile_layout().tileport().contains(moved) {
+			self.look_cursor = Some(moved);
+		}
+	}
+
+	/// Draws `text` in a box beside `anchor`, keeping the box on screen.
+	fn draw_tooltip(
+		&self,
+		ctx: &Context,
+		canvas: &mut Canvas,
+		text: &str,
+		anchor: ScreenPoint,
+	) -> GameResult {
+		let text = Text::new(text);
+		let text_size = text.measure(ctx)?;
+		let size = ScreenVector::new(
+			text_size.x + 2.0 * TOOLTIP_PADDING,
+			text_size.y + 2.0 * TOOLTIP_PADDING,
+		);
+		let (width, height) = ctx.gfx.drawable_size();
+		let pos = ScreenPoint::new(
+			(anchor.x + 16.0).min(width - size.x),
+			(anchor.y + 16.0).min(height - size.y),
+		);
+		canvas.draw(
+			&self.meshes.panel,
+			DrawParam::new()
+				.dest(pos)
+				.scale(size)
+				.color(Color::from_rgba(0, 0, 0, 192)),
+		);
+		canvas.draw(
+			&text,
+			DrawParam::new().dest(
+				pos + ScreenVector::new(TOOLTIP_PADDING, TOOLTIP_PADDING),
+			),
+		);
+		Ok(())
+	}
+}
+
+/// The window's current drawable area.
+fn screen(ctx: &Context) -> ScreenRectangle {
+	let (width, height) = ctx.gfx.drawable_size();
+	ScreenRectangle {
+		pos: ScreenPoint::new(0.0, 0.0),
+		size: ScreenVector::new(width, height),
 	}
 }
 
 impl event::EventHandler<ggez::GameError> for GameState {
-	fn update(&mut self, _ctx: &mut Context) -> GameResult {
+	fn update(&mut self, ctx: &mut Context) -> GameResult {
+		self.animations.advance(ctx.time.delta());
+		while ctx.time.check_update_time(TRAVEL_STEPS_PER_SECOND) {
+			// Let each step of travel play out before taking the next.
+			if !self.animations.playing() {
+				self.travel();
+			}
+		}
+		Ok(())
+	}
+
+	fn resize_event(
+		&mut self,
+		ctx: &mut Context,
+		_width: f32,
+		_height: f32,
+	) -> GameResult {
+		self.fit_to_window(ctx);
 		Ok(())
 	}
 
@@ -56,10 +273,29 @@ impl event::EventHandler<ggez::GameError> for GameState {
 			return Ok(());
 		};
 
+		// Look mode takes over the keyboard until it's left.
+		if let Some(cursor) = self.look_cursor {
+			self.look(cursor, keycode);
+			return Ok(());
+		}
+
 		if let KeyCode::Escape = keycode {
 			ctx.request_quit();
 		}
 
+		if let KeyCode::Tab = keycode {
+			self.switch_presentation();
+			return Ok(());
+		}
+
+		// Any key interrupts travel.
+		self.destination = None;
+
+		if let KeyCode::L = keycode {
+			self.look_cursor = Some(self.player.borrow().coords);
+			return Ok(());
+		}
+
 		// Disable player actions when dead.
 		if self.player.borrow().dead() {
 			return Ok(());
@@ -79,9 +315,101 @@ impl event::EventHandler<ggez::GameError> for GameState {
 		Ok(())
 	}
 
+	fn mouse_motion_event(
+		&mut self,
+		_ctx: &mut Context,
+		x: f32,
+		y: f32,
+		_dx: f32,
+		_dy: f32,
+	) -> GameResult {
+		self.mouse = Some(ScreenPoint::new(x, y));
+		Ok(())
+	}
+
+	fn mouse_button_down_event(
+		&mut self,
+		_ctx: &mut Context,
+		button: MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult {
+		if button != MouseButton::Left || self.player.borrow().dead() {
+			return Ok(());
+		}
+		// Travel to any floor the player remembers.
+		let coords = self.level.tile_layout().to_tile(ScreenPoint::new(x, y));
+		if let Some(coords) = coords.filter(|coords| {
+			matches!(self.level.remembered(*coords), Some(Tile::Floor(_)))
+		}) {
+			self.destination = Some(coords);
+		}
+		Ok(())
+	}
+
 	fn draw(&mut self, ctx: &mut Context) -> GameResult {
 		let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
-		self.level.draw(&mut canvas, &self.meshes);
+		let hovered = self
+			.mouse
+			.and_then(|mouse| self.level.tile_layout().to_tile(mouse));
+		let tile_layout = self.level.tile_layout();
+		let mut renderer: Box<dyn Renderer> =
+			match (self.presentation, &mut self.tileset) {
+				(Presentation::Sprites, Some(tileset)) => {
+					Box::new(SpriteRenderer::new(
+						&mut canvas,
+						tileset,
+						&self.meshes,
+						tile_layout,
+					))
+				}
+				(Presentation::Glyphs, _) => Box::new(GlyphRenderer::new(
+					ctx,
+					&mut canvas,
+					&self.meshes,
+					tile_layout,
+				)?),
+				_ => Box::new(MeshRenderer::new(
+					&mut canvas,
+					&mut self.meshes,
+					tile_layout,
+				)),
+			};
+		self.level
+			.draw(renderer.as_mut(), |creature| self.animations.pose(creature));
+		for (species, coords, pose) in self.animations.dying() {
+			if self.level.in_view(coords) {
+				renderer.draw_creature(coords, species, pose);
+			}
+		}
+		if let Some(coords) = self.look_cursor.or(hovered) {
+			renderer.draw_highlight(coords);
+		}
+		renderer.finish();
+		hud::draw(
+			ctx,
+			&mut canvas,
+			&self.meshes,
+			hud::split(screen(ctx)).1,
+			&self.player,
+			&self.level,
+			self.room_feeling.as_deref(),
+		);
+		// Describe the tile being looked at, or else the hovered tile.
+		if let Some(cursor) = self.look_cursor {
+			let description = self
+				.level
+				.describe(cursor)
+				.unwrap_or_else(|| "You haven't seen this.".to_string());
+			let screen_tile = self.level.tile_layout().to_screen(cursor);
+			let anchor = screen_tile.pos + screen_tile.size;
+			self.draw_tooltip(ctx, &mut canvas, &description, anchor)?;
+		} else if let (Some(mouse), Some(description)) = (
+			self.mouse,
+			hovered.and_then(|coords| self.level.describe(coords)),
+		) {
+			self.draw_tooltip(ctx, &mut canvas, &description, mouse)?;
+		}
 		canvas.finish(ctx)
 	}
 }
@@ -0,0 +1,319 @@
+mod bsp;
+mod caves;
+mod corridor;
+mod grid;
+mod room_graph;
+mod rooms;
+mod shape;
+mod vault;
+mod wfc;
+
+use std::{collections::HashMap, fmt};
+
+use rand_pcg::Pcg32;
+
+use crate::{
+	creature::Species,
+	geometry::{TilePoint, TileRectangle, TileVector, NEIGHBORS_OFFSETS_EIGHT},
+	level::{Floor, Tile},
+};
+
+pub use bsp::BspGenerator;
+pub use caves::CaveGenerator;
+pub use room_graph::{Corridor, RoomGraph, RoomNode, RoomRole};
+pub use rooms::RoomGenerator;
+pub use shape::RoomShape;
+pub use vault::Vault;
+pub use wfc::WfcGenerator;
+
+/// An algorithm for laying out a level's terrain.
+pub trait LevelGenerator {
+	/// Checks that the generator's settings can produce a level covering
+	/// `tileport`, so that [`LevelGenerator::generate`] won't panic.
+	fn validate(&self, tileport: TileRectangle) -> Result<(), GenerationError>;
+
+	/// Generates terrain covering `tileport`, which must have been validated.
+	/// May fail if randomness produces an unusable layout, in which case
+	/// generating again may succeed.
+	fn generate(
+		&self,
+		tileport: TileRectangle,
+		rng: &mut Pcg32,
+	) -> Result<Layout, GenerationError>;
+}
+
+/// Reasons level generation can fail.
+#[derive(Debug)]
+pub enum GenerationError {
+	/// The tileport is too small to hold a floor tile surrounded by walls.
+	TileportTooSmall(TileVector),
+	/// The on-screen tile size isn't positive.
+	InvalidTileSize(f32),
+	/// The minimum room size is less than one or greater than the maximum.
+	InvalidRoomSizes { min: i32, max: i32 },
+	/// Rooms of the maximum size can't fit within the level's floor.
+	RoomTooLarge { max: i32, floor: TileVector },
+	/// A binary space partitioning leaf, which holds a room of the minimum
+	/// size plus a margin, can't fit within the level's floor.
+	LeafTooLarge { size: i32, floor: TileVector },
+	/// Wave function collapse patterns can't fit within the level's floor.
+	PatternTooLarge { size: usize, floor: TileVector },
+	/// The wave function collapse pattern size is zero or larger than the
+	/// sample.
+	InvalidPatternSize { size: usize, sample: TileVector },
+	/// A ratio or probability setting is out of range.
+	InvalidRatio { name: &'static str, value: f32 },
+	/// A spawn table entry's group size range is empty.
+	EmptyGroupSize(Species),
+	/// The generation config allows no attempts.
+	NoAttempts,
+	/// Wave function collapse is allowed no attempts.
+	NoCollapseAttempts,
+	/// Wave function collapse failed to produce a usable level within its
+	/// attempt limit.
+	CollapseFailed,
+	/// The generated level has no open floor.
+	NoOpenTiles,
+	/// Some of the generated level's floor can't be reached from the start.
+	/// Holds the number of corridors that couldn't be routed, which may explain
+	/// why.
+	Disconnected {
+		components: usize,
+		failed_routes: usize,
+	},
+	/// Too little of the generated level is open floor.
+	FloorRatioTooLow { ratio: f32, min: f32 },
+	/// The generated level has too many dead ends.
+	TooManyDeadEnds { count: usize, max: usize },
+	/// The generated level's estimated longest path is too short.
+	PathTooShort { length: isize, min: isize },
+	/// Every attempt to generate a level failed. Holds the last failure.
+	AttemptsExhausted {
+		attempts: u32,
+		last: Box<GenerationError>,
+	},
+}
+
+impl fmt::Display for GenerationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			GenerationError::TileportTooSmall(size) => {
+				write!(f, "tileport of size {}x{} is too small", size.x, size.y)
+			}
+			GenerationError::InvalidTileSize(size) => {
+				write!(f, "tile size must be positive, but is {size}")
+			}
+			GenerationError::InvalidRoomSizes { min, max } => {
+				write!(f, "invalid room sizes: min {min}, max {max}")
+			}
+			GenerationError::RoomTooLarge { max, floor } => write!(
+				f,
+				"rooms up to size {max} don't fit in a {}x{} floor",
+				floor.x, floor.y
+			),
+			GenerationError::LeafTooLarge { size, floor } => write!(
+				f,
+				"partition leaves of size {size} don't fit in a {}x{} floor",
+				floor.x, floor.y
+			),
+			GenerationError::PatternTooLarge { size, floor } => write!(
+				f,
+				"patterns of size {size} don't fit in a {}x{} floor",
+				floor.x, floor.y
+			),
+			GenerationError::InvalidRatio { name, value } => {
+				write!(f, "{name} must be between 0 and 1, but is {value}")
+			}
+			GenerationError::EmptyGroupSize(species) => {
+				write!(f, "group size range for {species:?} is empty")
+			}
+			GenerationError::InvalidPatternSize { size, sample } => write!(
+				f,
+				"pattern size {size} must be between 1 and the size of the \
+				 {}x{} sample",
+				sample.x, sample.y
+			),
+			GenerationError::NoAttempts => {
+				write!(f, "generation must be allowed at least one attempt")
+			}
+			GenerationError::NoCollapseAttempts => write!(
+				f,
+				"wave function collapse must be allowed at least one attempt"
+			),
+			GenerationError::CollapseFailed => {
+				write!(f, "wave function collapse failed")
+			}
+			GenerationError::NoOpenTiles => {
+				write!(f, "generated level has no open tiles")
+			}
+			GenerationError::Disconnected {
+				components,
+				failed_routes,
+			} => {
+				write!(
+					f,
+					"generated level is split into {components} disconnected \
+					 parts"
+				)?;
+				if *failed_routes > 0 {
+					write!(
+						f,
+						" ({failed_routes} corridors couldn't be routed)"
+					)?;
+				}
+				Ok(())
+			}
+			GenerationError::FloorRatioTooLow { ratio, min } => write!(
+				f,
+				"generated level's floor ratio {ratio:.2} is below {min:.2}"
+			),
+			GenerationError::TooManyDeadEnds { count, max } => write!(
+				f,
+				"generated level has {count} dead ends, more than {max}"
+			),
+			GenerationError::PathTooShort { length, min } => write!(
+				f,
+				"generated level's estimated longest path {length} is shorter \
+				 than {min}"
+			),
+			GenerationError::AttemptsExhausted { attempts, last } => write!(
+				f,
+				"level generation failed after {attempts} attempts: {last}"
+			),
+		}
+	}
+}
+
+impl std::error::Error for GenerationError {}
+
+/// Checks that `value`, a setting called `name`, is between 0 and 1.
+pub fn check_ratio(
+	name: &'static str,
+	value: f32,
+) -> Result<(), GenerationError> {
+	if (0.0..=1.0).contains(&value) {
+		Ok(())
+	} else {
+		Err(GenerationError::InvalidRatio { name, value })
+	}
+}
+
+/// Checks that rooms between `min` and `max` tiles wide fit within `floor`.
+pub fn check_room_sizes(
+	min: i32,
+	max: i32,
+	floor: TileRectangle,
+) -> Result<(), GenerationError> {
+	if min < 1 || min > max {
+		return Err(GenerationError::InvalidRoomSizes { min, max });
+	}
+	if max > floor.size.x || max > floor.size.y {
+		return Err(GenerationError::RoomTooLarge {
+			max,
+			floor: floor.size,
+		});
+	}
+	Ok(())
+}
+
+/// The terrain produced by a [`LevelGenerator`], along with its room graph and
+/// statistics about how it was generated.
+#[derive(Default)]
+pub struct Layout {
+	pub terrain: HashMap<TilePoint, Tile>,
+	/// Points where a monster must be spawned.
+	pub monster_spawns: Vec<TilePoint>,
+	/// Points where an item must be placed.
+	pub item_spawns: Vec<TilePoint>,
+	/// The level's rooms and the corridors between them, if it has any.
+	pub rooms: RoomGraph,
+	pub stats: GenerationStats,
+}
+
+/// A room in a generated level.
+pub struct Room {
+	/// The bounding rectangle of the room's floor. The room's walls surround
+	/// the floor.
+	pub floor: TileRectangle,
+	/// The shape of the floor within its bounding rectangle.
+	pub shape: RoomShape,
+	/// The tiles in the room's walls through which corridors may enter, or
+	/// `None` if corridors may enter anywhere.
+	pub doors: Option<Vec<TilePoint>>,
+}
+
+impl Room {
+	/// A rectangular room with the given `floor` that corridors may enter
+	/// anywhere.
+	pub fn new(floor: TileRectangle) -> Room {
+		Room {
+			floor,
+			shape: RoomShape::Rectangle,
+			doors: None,
+		}
+	}
+
+	/// Whether `coords` is one of the room's floor tiles.
+	pub fn is_floor(&self, coords: TilePoint) -> bool {
+		self.floor.contains(coords)
+			&& self
+				.shape
+				.contains(coords - self.floor.pos, self.floor.size)
+	}
+
+	/// The room's floor tiles, in row-major order.
+	pub fn floor_tiles(&self) -> impl Iterator<Item = TilePoint> + '_ {
+		self.floor.points().filter(|coords| self.is_floor(*coords))
+	}
+
+	/// Whether `coords` is within the room's footprint: its floor plus the
+	/// walls surrounding it.
+	pub fn footprint_contains(&self, coords: TilePoint) -> bool {
+		self.floor.expanded(1).contains(coords)
+			&& (self.is_floor(coords)
+				|| NEIGHBORS_OFFSETS_EIGHT
+					.iter()
+					.any(|offset| self.is_floor(coords + *offset)))
+	}
+
+	/// Whether the floors of `self` and `other` overlap or are adjacent,
+	/// including diagonally.
+	pub fn touching(&self, other: &Room) -> bool {
+		self.floor.touching(other.floor)
+			&& self
+				.floor_tiles()
+				.any(|coords| other.footprint_contains(coords))
+	}
+}
+
+/// Statistics describing the layout of a generated level, useful for tuning a
+/// [`LevelGenerator`].
+#[derive(Default)]
+pub struct GenerationStats {
+	/// The number of rooms in the level.
+	pub rooms: usize,
+	/// The number of independent loops in the graph of rooms connected by
+	/// corridors, i.e. the graph's cyclomatic number.
+	pub cycles: usize,
+	/// The number of corridors that couldn't be routed between the rooms they
+	/// were meant to connect, e.g. because other rooms were in the way.
+	pub failed_routes: usize,
+}
+
+/// Makes `coords` a floor tile of type `floor`, surrounding it with walls
+/// wherever there isn't already terrain.
+pub fn make_floor(
+	terrain: &mut HashMap<TilePoint, Tile>,
+	coords: TilePoint,
+	floor: Floor,
+) {
+	for x in coords.x - 1..=coords.x + 1 {
+		for y in coords.y - 1..=coords.y + 1 {
+			if x == coords.x && y == coords.y {
+				terrain.insert(coords, Tile::Floor(floor));
+			} else {
+				terrain.entry(TilePoint::new(x, y)).or_insert(Tile::Wall);
+			}
+		}
+	}
+}
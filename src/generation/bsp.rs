@@ -0,0 +1,256 @@
+use std::{
+	collections::{HashMap, HashSet},
+	ops::Range,
+};
+
+use rand::Rng;
+use rand_pcg::Pcg32;
+
+use crate::{
+	generation::{
+		check_ratio, check_room_sizes, corridor, make_floor, Corridor,
+		GenerationError, GenerationStats, Layout, LevelGenerator, Room,
+		RoomGraph, RoomNode, RoomRole, RoomShape,
+	},
+	geometry::{TileRectangle, TileVector},
+	level::Floor,
+};
+
+/// Generates levels by recursively splitting the level region in two (binary
+/// space partitioning), placing one room in each leaf region, and connecting
+/// sibling regions with corridors. Compared to [`RoomGenerator`], rooms are
+/// spread more evenly and coverage is more predictable.
+///
+/// [`RoomGenerator`]: crate::generation::RoomGenerator
+pub struct BspGenerator {
+	/// Minimum width of a room's floor.
+	pub min_room_size: i32,
+	/// Maximum length of a room's floor. Regions are split until they're no
+	/// larger than needed to hold a room of this size.
+	pub max_room_size: i32,
+	/// The proportion of rooms given a random non-rectangular shape.
+	pub shaped_room_ratio: f32,
+}
+
+impl LevelGenerator for BspGenerator {
+	fn validate(&self, tileport: TileRectangle) -> Result<(), GenerationError> {
+		let floor = tileport.expanded(-1);
+		check_room_sizes(self.min_room_size, self.max_room_size, floor)?;
+		// Without space for one leaf, partitioning places no rooms.
+		let size = self.min_leaf_size();
+		if floor.size.x < size || floor.size.y < size {
+			return Err(GenerationError::LeafTooLarge {
+				size,
+				floor: floor.size,
+			});
+		}
+		check_ratio("shaped_room_ratio", self.shaped_room_ratio)
+	}
+
+	fn generate(
+		&self,
+		tileport: TileRectangle,
+		rng: &mut Pcg32,
+	) -> Result<Layout, GenerationError> {
+		// Leave a one-tile border around the floor for outer walls.
+		let floor = tileport.expanded(-1);
+
+		let mut rooms = Vec::new();
+		let mut connections = Vec::new();
+		self.partition(floor, rng, &mut rooms, &mut connections);
+
+		// Open the floor of each room.
+		let mut terrain = HashMap::new();
+		let floors = rooms
+			.iter()
+			.map(|room| {
+				let floor = if rng.gen() { Floor::Wood } else { Floor::Grass };
+				for coords in room.floor_tiles() {
+					make_floor(&mut terrain, coords, floor);
+				}
+				floor
+			})
+			.collect::<Vec<_>>();
+
+		// Connect each pair of sibling regions, using the closest pair of
+		// rooms that can be connected.
+		let mut corridors = HashSet::new();
+		let mut graph_corridors = Vec::new();
+		let mut failed_routes = 0;
+		for candidates in connections {
+			for (i, j) in candidates {
+				if let Some(corridor) =
+					corridor::route(&rooms, i, j, floor, &corridors, rng)
+				{
+					for coords in &corridor {
+						make_floor(&mut terrain, *coords, Floor::Stone);
+						corridors.insert(*coords);
+					}
+					graph_corridors.push(Corridor {
+						rooms: [i, j],
+						tiles: corridor,
+					});
+					break;
+				}
+				failed_routes += 1;
+			}
+		}
+
+		let stats = GenerationStats {
+			rooms: rooms.len(),
+			cycles: 0,
+			failed_routes,
+		};
+		let nodes = rooms
+			.into_iter()
+			.zip(floors)
+			.map(|(room, floor)| RoomNode {
+				room,
+				floor: Some(floor),
+				role: RoomRole::Ordinary,
+			})
+			.collect();
+		Ok(Layout {
+			terrain,
+			rooms: RoomGraph::new(nodes, graph_corridors, rng),
+			stats,
+			..Default::default()
+		})
+	}
+}
+
+impl BspGenerator {
+	/// The smallest region that can hold a room. Leaves need space for a room
+	/// plus a one-tile margin on each side, so that rooms in neighboring leaves
+	/// never touch.
+	fn min_leaf_size(&self) -> i32 {
+		self.min_room_size + 2
+	}
+
+	/// Recursively splits `region`, adding a room to `rooms` for each leaf
+	/// region. For each split, adds to `connections` the pairs of rooms that
+	/// could connect the two halves, closest first. Returns the range of
+	/// indices into `rooms` of the rooms within `region`.
+	fn partition(
+		&self,
+		region: TileRectangle,
+		rng: &mut Pcg32,
+		rooms: &mut Vec<Room>,
+		connections: &mut Vec<Vec<(usize, usize)>>,
+	) -> Range<usize> {
+		let min_leaf_size = self.min_leaf_size();
+		let max_leaf_size = self.max_room_size + 2;
+		let split_x =
+			region.size.x > max_leaf_size && region.size.x >= 2 * min_leaf_size;
+		let split_y =
+			region.size.y > max_leaf_size && region.size.y >= 2 * min_leaf_size;
+
+		// Split along the longer axis, if either needs splitting.
+		let vertical_cut = match (split_x, split_y) {
+			(false, false) => None,
+			(true, false) => Some(true),
+			(false, true) => Some(false),
+			(true, true) => Some(region.size.x >= region.size.y),
+		};
+		let Some(vertical_cut) = vertical_cut else {
+			// This is a leaf. Place a room within it if there's space.
+			let start = rooms.len();
+			if region.size.x >= min_leaf_size && region.size.y >= min_leaf_size
+			{
+				let size = TileVector::new(
+					rng.gen_range(
+						self.min_room_size
+							..=self.max_room_size.min(region.size.x - 2),
+					),
+					rng.gen_range(
+						self.min_room_size
+							..=self.max_room_size.min(region.size.y - 2),
+					),
+				);
+				let slack = region.size - size - TileVector::new(2, 2);
+				let mut room = Room::new(TileRectangle {
+					pos: region.pos
+						+ TileVector::new(
+							1 + rng.gen_range(0..=slack.x),
+							1 + rng.gen_range(0..=slack.y),
+						),
+					size,
+				});
+				if rng.gen_range(0.0..1.0) < self.shaped_room_ratio {
+					room.shape = RoomShape::random(size, rng);
+				}
+				rooms.push(room);
+			}
+			return start..rooms.len();
+		};
+
+		let (first, second) = if vertical_cut {
+			let cut =
+				rng.gen_range(min_leaf_size..=region.size.x - min_leaf_size);
+			(
+				TileRectangle {
+					pos: region.pos,
+					size: TileVector::new(cut, region.size.y),
+				},
+				TileRectangle {
+					pos: region.pos + TileVector::new(cut, 0),
+					size: TileVector::new(region.size.x - cut, region.size.y),
+				},
+			)
+		} else {
+			let cut =
+				rng.gen_range(min_leaf_size..=region.size.y - min_leaf_size);
+			(
+				TileRectangle {
+					pos: region.pos,
+					size: TileVector::new(region.size.x, cut),
+				},
+				TileRectangle {
+					pos: region.pos + TileVector::new(0, cut),
+					size: TileVector::new(region.size.x, region.size.y - cut),
+				},
+			)
+		};
+
+		let first = self.partition(first, rng, rooms, connections);
+		let second = self.partition(second, rng, rooms, connections);
+		let mut candidates = first
+			.clone()
+			.flat_map(|i| second.clone().map(move |j| (i, j)))
+			.collect::<Vec<_>>();
+		candidates.sort_by_key(|&(i, j)| {
+			rooms[i].floor.intersection(rooms[j].floor).distance()
+		});
+		if !candidates.is_empty() {
+			connections.push(candidates);
+		}
+		first.start..second.end
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::geometry::TilePoint;
+
+	#[test]
+	fn rejects_floors_without_room_for_a_leaf() {
+		let generator = BspGenerator {
+			min_room_size: 3,
+			max_room_size: 3,
+			shaped_room_ratio: 0.0,
+		};
+		// The floor is the tileport less a one-tile border.
+		let tileport = |size| TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size: TileVector::new(size, size),
+		};
+		for size in [5, 6] {
+			assert!(matches!(
+				generator.validate(tileport(size)),
+				Err(GenerationError::LeafTooLarge { size: 5, .. })
+			));
+		}
+		assert!(generator.validate(tileport(7)).is_ok());
+	}
+}
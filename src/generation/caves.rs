@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use rand_pcg::Pcg32;
+
+use crate::{
+	generation::{
+		check_ratio,
+		grid::{tunnels, Grid},
+		make_floor, GenerationError, Layout, LevelGenerator,
+	},
+	geometry::TileRectangle,
+	level::Floor,
+};
+
+/// Generates organic cave levels by smoothing random noise with a cellular
+/// automaton.
+pub struct CaveGenerator {
+	/// The proportion of tiles initially filled with rock, before smoothing.
+	pub fill_ratio: f32,
+	/// The number of smoothing passes to apply to the initial noise.
+	pub smoothing_iterations: u32,
+	/// Open regions with fewer tiles than this are filled in with rock.
+	pub min_region_size: usize,
+	/// Whether to join the remaining open regions with tunnels. If false, only
+	/// the largest region is kept.
+	pub join_regions: bool,
+}
+
+impl LevelGenerator for CaveGenerator {
+	fn validate(
+		&self,
+		_tileport: TileRectangle,
+	) -> Result<(), GenerationError> {
+		check_ratio("fill_ratio", self.fill_ratio)
+	}
+
+	fn generate(
+		&self,
+		tileport: TileRectangle,
+		rng: &mut Pcg32,
+	) -> Result<Layout, GenerationError> {
+		// Leave a one-tile border around the floor for outer walls.
+		let floor = tileport.expanded(-1);
+
+		// Carve out caves from random noise.
+		let mut open =
+			Grid::new(floor, |_| rng.gen_range(0.0..1.0) >= self.fill_ratio);
+		for _ in 0..self.smoothing_iterations {
+			open = open.smoothed();
+		}
+
+		// Fill in regions that are too small or, if not joining regions, that
+		// aren't the largest.
+		let mut regions = open.regions();
+		regions.retain(|region| region.len() >= self.min_region_size);
+		if !self.join_regions {
+			regions.truncate(1);
+		}
+		let mut open = Grid::new(floor, |_| false);
+		for coords in regions.iter().flatten() {
+			open.set(*coords, true);
+		}
+		// Join each region to the nearest tile already connected to the
+		// largest region, largest regions first.
+		for coords in tunnels(regions) {
+			open.set(coords, true);
+		}
+
+		// Scatter organic patches of grass over the stone.
+		let mut grassy = Grid::new(floor, |_| rng.gen());
+		for _ in 0..self.smoothing_iterations {
+			grassy = grassy.smoothed();
+		}
+
+		let mut terrain = HashMap::new();
+		for coords in floor.points().filter(|coords| open.get(*coords)) {
+			let floor = if grassy.get(coords) {
+				Floor::Grass
+			} else {
+				Floor::Stone
+			};
+			make_floor(&mut terrain, coords, floor);
+		}
+		Ok(Layout {
+			terrain,
+			..Default::default()
+		})
+	}
+}
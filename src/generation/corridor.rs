@@ -0,0 +1,221 @@
+use std::{
+	cmp::Reverse,
+	collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use rand::Rng;
+use rand_pcg::Pcg32;
+
+use crate::{
+	generation::Room,
+	geometry::{TilePoint, TileRectangle, NEIGHBOR_OFFSETS_FOUR},
+};
+
+/// Cost of digging a corridor through solid rock.
+const DIG_COST: u32 = 2;
+/// Cost of following an existing corridor. This is cheaper than digging so
+/// that corridors tend to merge instead of running side by side.
+const REUSE_COST: u32 = 1;
+/// Extra cost for changing direction, which favors long straight hallways over
+/// staircases.
+const TURN_COST: u32 = 3;
+
+/// Routes a corridor between two rooms, identified by their indices `i` and `j`
+/// into `rooms`, using a cost-weighted search that avoids the footprints (the
+/// floor plus its surrounding walls) of every room. The corridor leaves room
+/// `i` through a single doorway tile in its wall and enters room `j` the same
+/// way, using the rooms' doors if they have any. Existing `corridors` may be
+/// reused, and the corridor stays within `bounds`.
+///
+/// Returns the corridor's tiles in order from `i`'s doorway to `j`'s doorway,
+/// or `None` if there is no route.
+pub fn route(
+	rooms: &[Room],
+	i: usize,
+	j: usize,
+	bounds: TileRectangle,
+	corridors: &HashSet<TilePoint>,
+	rng: &mut Pcg32,
+) -> Option<Vec<TilePoint>> {
+	let (from, to) = (&rooms[i], &rooms[j]);
+	// Whether `coords` is a valid doorway into `room`, given that `other` is
+	// the room at the other end of the corridor.
+	let is_entrance = |coords: TilePoint, room: &Room, other: &Room| {
+		is_doorway(coords, room)
+			&& bounds.contains(coords)
+			&& (!other.footprint_contains(coords) || is_doorway(coords, other))
+			&& rooms.iter().enumerate().all(|(k, room)| {
+				k == i || k == j || !room.footprint_contains(coords)
+			})
+	};
+	let is_open = |coords: TilePoint| {
+		bounds.contains(coords)
+			&& rooms.iter().all(|room| !room.footprint_contains(coords))
+	};
+
+	// Dijkstra's algorithm over (coordinates, heading) states, so that turns
+	// can be penalized. A heading of `None` means the corridor hasn't started
+	// moving yet.
+	type State = (TilePoint, Option<usize>);
+	let mut costs: HashMap<State, u32> = HashMap::new();
+	let mut parents: HashMap<State, State> = HashMap::new();
+	let mut queue = BinaryHeap::new();
+	for coords in from.floor.expanded(1).points() {
+		if is_entrance(coords, from, to) {
+			// Randomize starting costs a little so the choice of doorway
+			// varies between otherwise equivalent routes.
+			let cost = rng.gen_range(0..TURN_COST);
+			costs.insert((coords, None), cost);
+			queue.push(Reverse((cost, coords.x, coords.y, None)));
+		}
+	}
+	while let Some(Reverse((cost, x, y, heading))) = queue.pop() {
+		let coords = TilePoint::new(x, y);
+		let state = (coords, heading);
+		if costs.get(&state).is_some_and(|&best| best < cost) {
+			// This is a stale queue entry.
+			continue;
+		}
+		if is_entrance(coords, to, from) {
+			// Walk the parent links back to the start.
+			let mut path = vec![coords];
+			let mut state = state;
+			while let Some(&parent) = parents.get(&state) {
+				path.push(parent.0);
+				state = parent;
+			}
+			path.reverse();
+			return Some(path);
+		}
+		for (direction, offset) in NEIGHBOR_OFFSETS_FOUR.iter().enumerate() {
+			let neighbor = coords + *offset;
+			if !is_open(neighbor) && !is_entrance(neighbor, to, from) {
+				continue;
+			}
+			let mut neighbor_cost = cost
+				+ if corridors.contains(&neighbor) {
+					REUSE_COST
+				} else {
+					DIG_COST
+				};
+			if heading.is_some_and(|heading| heading != direction) {
+				neighbor_cost += TURN_COST;
+			}
+			let neighbor_state = (neighbor, Some(direction));
+			if costs
+				.get(&neighbor_state)
+				.is_some_and(|&best| best <= neighbor_cost)
+			{
+				continue;
+			}
+			costs.insert(neighbor_state, neighbor_cost);
+			parents.insert(neighbor_state, state);
+			queue.push(Reverse((
+				neighbor_cost,
+				neighbor.x,
+				neighbor.y,
+				Some(direction),
+			)));
+		}
+	}
+	None
+}
+
+/// Whether `coords` is in the wall surrounding `room`, adjacent (not just
+/// diagonally) to the room's floor, reachable from outside the room's
+/// footprint, and one of the room's doors, if it has any.
+fn is_doorway(coords: TilePoint, room: &Room) -> bool {
+	room.footprint_contains(coords)
+		&& !room.is_floor(coords)
+		&& NEIGHBOR_OFFSETS_FOUR
+			.iter()
+			.any(|offset| room.is_floor(coords + *offset))
+		&& NEIGHBOR_OFFSETS_FOUR
+			.iter()
+			.any(|offset| !room.footprint_contains(coords + *offset))
+		&& room
+			.doors
+			.as_ref()
+			.is_none_or(|doors| doors.contains(&coords))
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::SeedableRng;
+
+	use super::*;
+	use crate::geometry::TileVector;
+
+	fn room(x: i32, y: i32, width: i32, height: i32) -> Room {
+		Room::new(TileRectangle {
+			pos: TilePoint::new(x, y),
+			size: TileVector::new(width, height),
+		})
+	}
+
+	fn bounds() -> TileRectangle {
+		TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size: TileVector::new(30, 16),
+		}
+	}
+
+	/// Checks that `path` is a connected walk from a doorway of `from` to a
+	/// doorway of `to` that touches no other room.
+	fn check_path(path: &[TilePoint], rooms: &[Room], from: usize, to: usize) {
+		for step in path.windows(2) {
+			let offset = step[1] - step[0];
+			assert_eq!(offset.x.abs() + offset.y.abs(), 1, "{path:?}");
+		}
+		for (k, room) in rooms.iter().enumerate() {
+			let inside = path
+				.iter()
+				.filter(|coords| room.footprint_contains(**coords))
+				.copied()
+				.collect::<Vec<_>>();
+			let expected = if k == from {
+				vec![path[0]]
+			} else if k == to {
+				vec![path[path.len() - 1]]
+			} else {
+				vec![]
+			};
+			assert_eq!(inside, expected, "room {k}, path {path:?}");
+		}
+		assert!(is_doorway(path[0], &rooms[from]));
+		assert!(is_doorway(path[path.len() - 1], &rooms[to]));
+	}
+
+	#[test]
+	fn routes_around_rooms_in_between() {
+		let rooms = [room(2, 6, 3, 3), room(12, 2, 4, 11), room(24, 6, 3, 3)];
+		for seed in 0..10 {
+			let mut rng = Pcg32::seed_from_u64(seed);
+			let path = route(&rooms, 0, 2, bounds(), &HashSet::new(), &mut rng)
+				.unwrap();
+			check_path(&path, &rooms, 0, 2);
+		}
+	}
+
+	#[test]
+	fn enters_through_doors() {
+		let mut rooms = [room(2, 6, 3, 3), room(24, 6, 3, 3)];
+		let door = TilePoint::new(25, 9);
+		rooms[1].doors = Some(vec![door]);
+		let mut rng = Pcg32::seed_from_u64(0);
+		let path =
+			route(&rooms, 0, 1, bounds(), &HashSet::new(), &mut rng).unwrap();
+		check_path(&path, &rooms, 0, 1);
+		assert_eq!(path[path.len() - 1], door);
+	}
+
+	#[test]
+	fn fails_when_walled_off() {
+		// The room in between spans the whole height of the bounds.
+		let rooms = [room(2, 6, 3, 3), room(12, 1, 4, 14), room(24, 6, 3, 3)];
+		let mut rng = Pcg32::seed_from_u64(0);
+		assert!(
+			route(&rooms, 0, 2, bounds(), &HashSet::new(), &mut rng).is_none()
+		);
+	}
+}
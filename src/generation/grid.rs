@@ -0,0 +1,118 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::{
+	disjoint_sets::DisjointSets,
+	geometry::{
+		TilePoint, TileRectangle, TileVector, NEIGHBORS_OFFSETS_EIGHT,
+		TILE_DOWN, TILE_RIGHT,
+	},
+};
+
+/// A boolean value for each tile in a rectangle.
+#[derive(Clone)]
+pub struct Grid {
+	bounds: TileRectangle,
+	cells: Vec<bool>,
+}
+
+impl Grid {
+	/// Creates a grid over `bounds`, initializing each cell using `init`.
+	pub fn new(
+		bounds: TileRectangle,
+		init: impl FnMut(TilePoint) -> bool,
+	) -> Grid {
+		Grid {
+			bounds,
+			cells: bounds.points().map(init).collect(),
+		}
+	}
+
+	fn index(&self, coords: TilePoint) -> Option<usize> {
+		self.bounds.contains(coords).then(|| {
+			let offset = coords - self.bounds.pos;
+			(offset.y * self.bounds.size.x + offset.x) as usize
+		})
+	}
+
+	/// The value at `coords`, or false if `coords` is out of bounds.
+	pub fn get(&self, coords: TilePoint) -> bool {
+		self.index(coords).is_some_and(|i| self.cells[i])
+	}
+
+	pub fn set(&mut self, coords: TilePoint, value: bool) {
+		if let Some(i) = self.index(coords) {
+			self.cells[i] = value;
+		}
+	}
+
+	/// Applies one step of the cellular automaton: a cell becomes false if
+	/// most of its eight neighbors are false and true if most are true. Ties
+	/// leave the cell unchanged. Out-of-bounds neighbors count as false.
+	pub fn smoothed(&self) -> Grid {
+		Grid::new(self.bounds, |coords| {
+			let true_neighbors = NEIGHBORS_OFFSETS_EIGHT
+				.iter()
+				.filter(|offset| self.get(coords + **offset))
+				.count();
+			match true_neighbors {
+				0..=3 => false,
+				4 => self.get(coords),
+				_ => true,
+			}
+		})
+	}
+
+	/// The four-connected regions of true cells, largest first.
+	pub fn regions(&self) -> Vec<Vec<TilePoint>> {
+		let mut sets = DisjointSets::new(self.cells.len());
+		for coords in self.bounds.points().filter(|coords| self.get(*coords)) {
+			let i = self.index(coords).unwrap();
+			for neighbor in [coords + TILE_RIGHT, coords + TILE_DOWN] {
+				if self.get(neighbor) {
+					sets.merge(i, self.index(neighbor).unwrap());
+				}
+			}
+		}
+		let mut regions: HashMap<usize, Vec<TilePoint>> = HashMap::new();
+		for coords in self.bounds.points().filter(|coords| self.get(*coords)) {
+			let set = sets.find(self.index(coords).unwrap());
+			regions.entry(set).or_default().push(coords);
+		}
+		let mut regions = regions.into_values().collect::<Vec<_>>();
+		// Sort by position as well as size so the order is deterministic.
+		regions.sort_by_key(|region| {
+			(Reverse(region.len()), region[0].y, region[0].x)
+		});
+		regions
+	}
+}
+
+/// Finds L-shaped tunnels that join `regions` together, returning the tunnel
+/// tiles. Each region, in order, is joined to the nearest tile already
+/// connected to the first region.
+pub fn tunnels(regions: Vec<Vec<TilePoint>>) -> Vec<TilePoint> {
+	let mut tunnels = Vec::new();
+	let mut regions = regions.into_iter();
+	let mut connected = regions.next().unwrap_or_default();
+	for region in regions {
+		let (start, end) = region
+			.iter()
+			.flat_map(|start| connected.iter().map(move |end| (start, end)))
+			.min_by_key(|(start, end)| {
+				let offset = **end - **start;
+				offset.x.abs() + offset.y.abs()
+			})
+			.unwrap();
+		let mut coords = *start;
+		for target in [TilePoint::new(end.x, start.y), *end] {
+			while coords != target {
+				let offset = target - coords;
+				coords += TileVector::new(offset.x.signum(), offset.y.signum());
+				tunnels.push(coords);
+				connected.push(coords);
+			}
+		}
+		connected.extend(region);
+	}
+	tunnels
+}
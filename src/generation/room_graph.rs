@@ -0,0 +1,138 @@
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand_pcg::Pcg32;
+
+use crate::{generation::Room, geometry::TilePoint, level::Floor};
+
+/// The part a room plays in a level.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoomRole {
+	/// The room where the player starts.
+	Entrance,
+	/// A prefab vault.
+	Vault,
+	/// A room reachable through only one corridor.
+	DeadEnd,
+	/// Any other room.
+	Ordinary,
+}
+
+/// A room in a [`RoomGraph`].
+pub struct RoomNode {
+	pub room: Room,
+	/// The room's floor type, or `None` if the floor is mixed.
+	pub floor: Option<Floor>,
+	pub role: RoomRole,
+}
+
+impl RoomNode {
+	/// A short message describing the room, shown when entering it.
+	pub fn feeling(&self) -> String {
+		let floor = match self.floor {
+			Some(Floor::Grass) => "Grass grows underfoot.",
+			Some(Floor::Wood) => "Wooden boards creak underfoot.",
+			Some(Floor::Stone) => "Cold stone lies underfoot.",
+			None => "The floor is a patchwork of materials.",
+		};
+		match self.role {
+			RoomRole::Entrance => {
+				format!("You're back where you started. {floor}")
+			}
+			RoomRole::Vault => {
+				format!("This room was built with care. {floor}")
+			}
+			RoomRole::DeadEnd => {
+				format!("There's only one way out of here. {floor}")
+			}
+			RoomRole::Ordinary => floor.to_string(),
+		}
+	}
+}
+
+/// A corridor between two rooms in a [`RoomGraph`].
+pub struct Corridor {
+	/// The indices of the rooms at either end of the corridor.
+	pub rooms: [usize; 2],
+	/// The corridor's tiles, in order from the first room to the second.
+	pub tiles: Vec<TilePoint>,
+}
+
+/// The rooms of a generated level and the corridors connecting them.
+#[derive(Default)]
+pub struct RoomGraph {
+	pub rooms: Vec<RoomNode>,
+	pub corridors: Vec<Corridor>,
+}
+
+impl RoomGraph {
+	/// Builds a room graph, marking rooms with one corridor as dead ends and
+	/// choosing a random non-vault room as the entrance.
+	pub fn new(
+		mut rooms: Vec<RoomNode>,
+		corridors: Vec<Corridor>,
+		rng: &mut Pcg32,
+	) -> RoomGraph {
+		let mut degrees = vec![0; rooms.len()];
+		for corridor in &corridors {
+			for i in corridor.rooms {
+				degrees[i] += 1;
+			}
+		}
+		for (node, degree) in rooms.iter_mut().zip(degrees) {
+			if node.role == RoomRole::Ordinary && degree == 1 {
+				node.role = RoomRole::DeadEnd;
+			}
+		}
+		if let Some(entrance) = rooms
+			.iter_mut()
+			.filter(|node| node.role != RoomRole::Vault)
+			.choose(rng)
+		{
+			entrance.role = RoomRole::Entrance;
+		}
+		RoomGraph { rooms, corridors }
+	}
+
+	/// The index of the room whose floor contains `coords`, if any.
+	pub fn room_at(&self, coords: TilePoint) -> Option<usize> {
+		self.rooms
+			.iter()
+			.position(|node| node.room.is_floor(coords))
+	}
+
+	/// The index of the room with the given `role`, if any.
+	pub fn find(&self, role: RoomRole) -> Option<usize> {
+		self.rooms.iter().position(|node| node.role == role)
+	}
+
+	/// The indices of the rooms directly connected to room `i` by a corridor.
+	pub fn neighbors(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+		self.corridors
+			.iter()
+			.filter_map(move |corridor| match corridor.rooms {
+				[a, b] if a == i => Some(b),
+				[a, b] if b == i => Some(a),
+				_ => None,
+			})
+	}
+
+	/// A random next room to visit from `coords`: a neighbor of the room
+	/// containing `coords`, either end of the corridor containing `coords`, or
+	/// else any room.
+	pub fn next_room(
+		&self,
+		coords: TilePoint,
+		rng: &mut Pcg32,
+	) -> Option<usize> {
+		if let Some(i) = self.room_at(coords) {
+			return self.neighbors(i).choose(rng);
+		}
+		if let Some(corridor) = self
+			.corridors
+			.iter()
+			.find(|corridor| corridor.tiles.contains(&coords))
+		{
+			return corridor.rooms.choose(rng).copied();
+		}
+		(0..self.rooms.len()).choose(rng)
+	}
+}
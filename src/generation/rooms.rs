@@ -0,0 +1,374 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+use rand_pcg::Pcg32;
+
+use crate::{
+	disjoint_sets::DisjointSets,
+	generation::{
+		check_ratio, check_room_sizes, corridor, make_floor, Corridor,
+		GenerationError, GenerationStats, Layout, LevelGenerator, Room,
+		RoomGraph, RoomNode, RoomRole, RoomShape, Vault,
+	},
+	geometry::{
+		random_neighbor_offset_eight, RectangleIntersection, TileIntersection,
+		TilePoint, TileRectangle, TileVector,
+	},
+	level::{Floor, Tile},
+};
+
+/// Generates levels by scattering rooms and prefab vaults and connecting them
+/// with corridors.
+pub struct RoomGenerator {
+	/// The minimum allowable proportion of all tiles within the tileport to be
+	/// marked as floors. Additional rooms will be added until this proportion
+	/// is reached (up to a retry limit, in case additional rooms can't fit).
+	pub min_floor_ratio: f32,
+	/// Minimum width of a room's floor.
+	pub min_room_size: i32,
+	/// Maximum length of a room's floor.
+	pub max_room_size: i32,
+	/// The proportion of rooms given a random non-rectangular shape.
+	pub shaped_room_ratio: f32,
+	/// The number of extra connections to add once all rooms are connected,
+	/// as a proportion of the number of rooms. Each extra connection joins the
+	/// closest pair of rooms not already directly connected, creating a loop.
+	pub loop_ratio: f32,
+	/// Prefab vaults that may be stamped into the level, each at a random
+	/// orientation, before any other rooms are placed.
+	pub vaults: Vec<Vault>,
+	/// The chance that each vault is included in a level.
+	pub vault_chance: f32,
+}
+
+const MAX_ROOM_PLACEMENT_RETRIES: u32 = 100;
+
+impl LevelGenerator for RoomGenerator {
+	fn validate(&self, tileport: TileRectangle) -> Result<(), GenerationError> {
+		check_room_sizes(
+			self.min_room_size,
+			self.max_room_size,
+			tileport.expanded(-1),
+		)?;
+		check_ratio("min_floor_ratio", self.min_floor_ratio)?;
+		check_ratio("shaped_room_ratio", self.shaped_room_ratio)?;
+		check_ratio("vault_chance", self.vault_chance)?;
+		if self.loop_ratio < 0.0 {
+			return Err(GenerationError::InvalidRatio {
+				name: "loop_ratio",
+				value: self.loop_ratio,
+			});
+		}
+		Ok(())
+	}
+
+	fn generate(
+		&self,
+		tileport: TileRectangle,
+		rng: &mut Pcg32,
+	) -> Result<Layout, GenerationError> {
+		// Leave a one-tile border around the floor for outer walls.
+		let floor = TileRectangle {
+			pos: TilePoint {
+				x: tileport.pos.x + 1,
+				y: tileport.pos.y + 1,
+			},
+			size: TileVector::new(tileport.size.x - 2, tileport.size.y - 2),
+		};
+
+		let mut layout = Layout::default();
+		let mut rooms: Vec<Room> = Vec::new();
+		let mut floor_area = 0;
+
+		// Stamp vaults wherever they fit without touching each other. Vaults
+		// come first so that other rooms are placed around them.
+		for vault in &self.vaults {
+			if rng.gen_range(0.0..1.0) >= self.vault_chance {
+				continue;
+			}
+			let vault = vault.oriented(rng.gen_range(0..4), rng.gen());
+			let size = vault.size();
+			if size.x > floor.size.x || size.y > floor.size.y {
+				continue;
+			}
+			for _ in 0..MAX_ROOM_PLACEMENT_RETRIES {
+				let pos = TilePoint::new(
+					floor.pos.x + rng.gen_range(0..=floor.size.x - size.x),
+					floor.pos.y + rng.gen_range(0..=floor.size.y - size.y),
+				);
+				let room = vault.room(pos);
+				if rooms
+					.iter()
+					.all(|other| !room.floor.expanded(1).touching(other.floor))
+				{
+					vault.stamp(&mut layout, pos);
+					floor_area += vault.floor_area();
+					rooms.push(room);
+					break;
+				}
+			}
+		}
+		let vault_count = rooms.len();
+
+		// Add rooms until the target floor coverage is reached.
+		let total_area = floor.area();
+		let mut retries = 0;
+		while (floor_area as f32 / total_area as f32) < self.min_floor_ratio {
+			let mut new_room = {
+				let size = TileVector::new(
+					rng.gen_range(self.min_room_size..=self.max_room_size),
+					rng.gen_range(self.min_room_size..=self.max_room_size),
+				);
+				let mut room = Room::new(TileRectangle {
+					pos: TilePoint::new(
+						floor.pos.x + rng.gen_range(0..=floor.size.x - size.x),
+						floor.pos.y + rng.gen_range(0..=floor.size.y - size.y),
+					),
+					size,
+				});
+				if rng.gen_range(0.0..1.0) < self.shaped_room_ratio {
+					room.shape = RoomShape::random(size, rng);
+				}
+				room
+			};
+
+			// Nudge the room while it touches any existing rooms. (This uses an
+			// inefficient O(n^2) collision algorithm, but it should be good
+			// enough for the number of rooms we're dealing with.)
+			let nudge = random_neighbor_offset_eight(rng);
+			let mut nudging = true;
+			while nudging {
+				nudging = false;
+				for room in rooms.iter() {
+					if new_room.touching(room) {
+						nudging = true;
+						new_room.floor.pos += nudge;
+					}
+				}
+			}
+
+			// Crop the room to fit within the tileport. Only rectangles keep
+			// their shape when cropped, so other shapes must fit entirely.
+			match new_room.floor.intersection(floor) {
+				RectangleIntersection::Real(intersection)
+					if intersection == new_room.floor
+						|| matches!(new_room.shape, RoomShape::Rectangle) =>
+				{
+					new_room.floor = intersection;
+				}
+				_ => {
+					// Set the room's size to zero so it will be discarded.
+					new_room.floor.size = TileVector::new(0, 0);
+				}
+			}
+
+			// If the room is now too small, discard it and try again.
+			if new_room.floor.size.x < self.min_room_size
+				|| new_room.floor.size.y < self.min_room_size
+			{
+				retries += 1;
+				if retries > MAX_ROOM_PLACEMENT_RETRIES {
+					break;
+				} else {
+					continue;
+				}
+			}
+
+			retries = 0;
+			floor_area += new_room.floor_tiles().count() as i32;
+			rooms.push(new_room);
+		}
+
+		// Open the floor of each room. Vaults were already stamped and have
+		// mixed floors.
+		let floors = rooms
+			.iter()
+			.enumerate()
+			.map(|(i, room)| {
+				if i < vault_count {
+					return None;
+				}
+				let floor = if rng.gen() { Floor::Wood } else { Floor::Grass };
+				for coords in room.floor_tiles() {
+					make_floor(&mut layout.terrain, coords, floor);
+				}
+				Some(floor)
+			})
+			.collect::<Vec<_>>();
+
+		// Build a forest of disjoint sets of connected rooms. Initially, each
+		// room is in its own singleton set.
+		let mut connected_rooms = DisjointSets::new(rooms.len());
+
+		// Build an ordered queue of distances between rooms.
+		struct Edge {
+			i: usize,
+			j: usize,
+			intersection: TileIntersection,
+		}
+		let mut edges = Vec::new();
+		for (i, room1) in rooms.iter().enumerate() {
+			for (j, room2) in rooms.iter().enumerate().skip(i + 1) {
+				edges.push(Edge {
+					i,
+					j,
+					intersection: room1.floor.intersection(room2.floor),
+				});
+			}
+		}
+		edges.sort_by(|e1, e2| {
+			e2.intersection.distance().cmp(&e1.intersection.distance())
+		});
+
+		// Connect the closest two rooms until all rooms are connected.
+		let mut corridors = HashSet::new();
+		let mut connections = Vec::new();
+		// Routes a corridor between rooms `i` and `j`, around any other rooms
+		// in the way, returning whether a route was found.
+		let mut connect = |terrain: &mut HashMap<TilePoint, Tile>, i, j| {
+			let Some(corridor) =
+				corridor::route(&rooms, i, j, floor, &corridors, rng)
+			else {
+				return false;
+			};
+			for coords in &corridor {
+				make_floor(terrain, *coords, Floor::Stone);
+				corridors.insert(*coords);
+			}
+			connections.push(Corridor {
+				rooms: [i, j],
+				tiles: corridor,
+			});
+			true
+		};
+		let mut cycles = 0;
+		let mut failed_routes = 0;
+		while let Some(Edge { i, j, intersection }) = edges.pop() {
+			// If there's no route, try the next closest pair.
+			if !connect(&mut layout.terrain, i, j) {
+				failed_routes += 1;
+				continue;
+			}
+
+			// Connecting rooms that were already connected closes a loop.
+			if connected_rooms.find(i) == connected_rooms.find(j) {
+				cycles += 1;
+			}
+
+			// Merge the two connection sets. Stop if all rooms are connected
+			// and the most recently merged rooms were far enough apart.
+			if connected_rooms.merge(i, j) == rooms.len()
+				&& intersection.distance() > 2
+			{
+				break;
+			}
+		}
+
+		// Add loops by connecting some of the closest remaining pairs of rooms.
+		// Every remaining pair is already connected, so each new connection
+		// closes a loop.
+		let mut extra_connections =
+			(self.loop_ratio * rooms.len() as f32).round() as usize;
+		while extra_connections > 0 {
+			let Some(Edge { i, j, .. }) = edges.pop() else {
+				break;
+			};
+			if connect(&mut layout.terrain, i, j) {
+				cycles += 1;
+				extra_connections -= 1;
+			} else {
+				failed_routes += 1;
+			}
+		}
+
+		layout.stats = GenerationStats {
+			rooms: rooms.len(),
+			cycles,
+			failed_routes,
+		};
+		let nodes = rooms
+			.into_iter()
+			.zip(floors)
+			.enumerate()
+			.map(|(i, (room, floor))| RoomNode {
+				room,
+				floor,
+				role: if i < vault_count {
+					RoomRole::Vault
+				} else {
+					RoomRole::Ordinary
+				},
+			})
+			.collect();
+		layout.rooms = RoomGraph::new(nodes, connections, rng);
+		Ok(layout)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::SeedableRng;
+
+	use super::*;
+
+	fn generate(loop_ratio: f32, seed: u64) -> Layout {
+		let generator = RoomGenerator {
+			min_floor_ratio: 0.4,
+			min_room_size: 3,
+			max_room_size: 15,
+			shaped_room_ratio: 0.0,
+			loop_ratio,
+			vaults: Vec::new(),
+			vault_chance: 0.0,
+		};
+		let tileport = TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size: TileVector::new(64, 36),
+		};
+		generator
+			.generate(tileport, &mut Pcg32::seed_from_u64(seed))
+			.unwrap()
+	}
+
+	#[test]
+	fn counts_each_loop_in_the_room_graph() {
+		for seed in 0..5 {
+			for loop_ratio in [0.0, 0.2, 0.5] {
+				let layout = generate(loop_ratio, seed);
+				// Each corridor either joins two groups of rooms or closes a
+				// loop.
+				let mut groups = DisjointSets::new(layout.stats.rooms);
+				let mut loops = 0;
+				for corridor in &layout.rooms.corridors {
+					let [i, j] = corridor.rooms;
+					if groups.find(i) == groups.find(j) {
+						loops += 1;
+					} else {
+						groups.merge(i, j);
+					}
+				}
+				assert_eq!(
+					layout.stats.cycles, loops,
+					"seed {seed}, loop ratio {loop_ratio}"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn loop_ratio_adds_extra_connections() {
+		for seed in 0..5 {
+			let base = generate(0.0, seed).stats;
+			for loop_ratio in [0.2, 0.5] {
+				let stats = generate(loop_ratio, seed).stats;
+				assert_eq!(stats.rooms, base.rooms);
+				let extra = (loop_ratio * stats.rooms as f32).round() as usize;
+				assert_eq!(
+					stats.cycles,
+					base.cycles + extra,
+					"seed {seed}, loop ratio {loop_ratio}"
+				);
+			}
+		}
+	}
+}
@@ -0,0 +1,226 @@
+use rand::Rng;
+use rand_pcg::Pcg32;
+
+use crate::{
+	generation::grid::Grid,
+	geometry::{TilePoint, TileRectangle, TileVector},
+};
+
+/// The proportion of cells that start open when carving a cave pocket.
+const CAVE_OPEN_RATIO: f64 = 0.65;
+/// The number of cellular automaton steps used to carve a cave pocket.
+const CAVE_SMOOTHING_ITERATIONS: u32 = 2;
+
+/// The shape of a room's floor within its bounding rectangle.
+#[derive(Clone)]
+pub enum RoomShape {
+	/// The whole rectangle.
+	Rectangle,
+	/// The ellipse inscribed in the rectangle.
+	Circle,
+	/// The middle third of the rectangle's rows together with the middle third
+	/// of its columns.
+	Cross,
+	/// The rectangle minus one quadrant. `notch` is the index of the missing
+	/// quadrant: top-left, top-right, bottom-left, or bottom-right.
+	LShape { notch: usize },
+	/// The whole rectangle, except for pillars of wall on every other row and
+	/// column, away from the edges.
+	Pillared,
+	/// The tiles set in a grid, such as a pocket carved by a cellular
+	/// automaton or a vault's floor. The grid's bounds are relative to the
+	/// rectangle's top-left corner.
+	Mask(Grid),
+}
+
+impl RoomShape {
+	/// A random non-rectangular shape for a room of the given `size`.
+	pub fn random(size: TileVector, rng: &mut Pcg32) -> RoomShape {
+		match rng.gen_range(0..5) {
+			0 => RoomShape::Circle,
+			1 => RoomShape::Cross,
+			2 => RoomShape::LShape {
+				notch: rng.gen_range(0..4),
+			},
+			3 => RoomShape::Pillared,
+			_ => RoomShape::cave(size, rng),
+		}
+	}
+
+	/// A cave pocket filling as much of a `size` rectangle as the automaton
+	/// allows, or a plain rectangle if the automaton leaves nothing open.
+	fn cave(size: TileVector, rng: &mut Pcg32) -> RoomShape {
+		let bounds = TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size,
+		};
+		let mut open =
+			Grid::new(bounds, |_| rng.gen_range(0.0..1.0) < CAVE_OPEN_RATIO);
+		for _ in 0..CAVE_SMOOTHING_ITERATIONS {
+			open = open.smoothed();
+		}
+		// Keep only the largest pocket so that the room is connected.
+		let Some(pocket) = open.regions().into_iter().next() else {
+			return RoomShape::Rectangle;
+		};
+		let mut cave = Grid::new(bounds, |_| false);
+		for coords in pocket {
+			cave.set(coords, true);
+		}
+		RoomShape::Mask(cave)
+	}
+
+	/// Whether the tile at `offset` from the top-left corner of a `size`
+	/// rectangle is part of this shape. `offset` must be within the rectangle.
+	pub fn contains(&self, offset: TileVector, size: TileVector) -> bool {
+		match self {
+			RoomShape::Rectangle => true,
+			RoomShape::Circle => {
+				// Measure from tile centers, normalized so the ellipse has
+				// unit radius.
+				let dx = (2 * offset.x + 1 - size.x) as f32 / size.x as f32;
+				let dy = (2 * offset.y + 1 - size.y) as f32 / size.y as f32;
+				dx * dx + dy * dy <= 1.0
+			}
+			RoomShape::Cross => {
+				let in_band = |offset: i32, size: i32| {
+					size / 3 <= offset && offset < size - size / 3
+				};
+				in_band(offset.x, size.x) || in_band(offset.y, size.y)
+			}
+			RoomShape::LShape { notch } => {
+				let notch_x = if notch % 2 == 0 {
+					offset.x < size.x / 2
+				} else {
+					offset.x >= size.x - size.x / 2
+				};
+				let notch_y = if notch / 2 == 0 {
+					offset.y < size.y / 2
+				} else {
+					offset.y >= size.y - size.y / 2
+				};
+				!(notch_x && notch_y)
+			}
+			RoomShape::Pillared => {
+				let is_pillar = |offset: i32, size: i32| {
+					offset % 2 == 1 && offset < size - 1
+				};
+				!(is_pillar(offset.x, size.x) && is_pillar(offset.y, size.y))
+			}
+			RoomShape::Mask(grid) => {
+				grid.get(TilePoint::new(offset.x, offset.y))
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::generation::Room;
+
+	/// `shape` in a `width` x `height` rectangle, with `#` for floor.
+	fn floor(shape: &RoomShape, width: i32, height: i32) -> String {
+		let size = TileVector::new(width, height);
+		(0..height)
+			.map(|y| {
+				(0..width)
+					.map(|x| {
+						let inside =
+							shape.contains(TileVector::new(x, y), size);
+						if inside {
+							'#'
+						} else {
+							' '
+						}
+					})
+					.chain(['\n'])
+					.collect::<String>()
+			})
+			.collect()
+	}
+
+	#[test]
+	fn shapes_cover_the_expected_floor() {
+		let mut mask = Grid::new(
+			TileRectangle {
+				pos: TilePoint::new(0, 0),
+				size: TileVector::new(4, 3),
+			},
+			|_| false,
+		);
+		for coords in [(0, 0), (1, 1), (2, 1), (3, 2)] {
+			mask.set(TilePoint::new(coords.0, coords.1), true);
+		}
+		let cases = [
+			(RoomShape::Rectangle, 4, 3, "####\n####\n####\n"),
+			(
+				RoomShape::Circle,
+				5,
+				5,
+				" ### \n#####\n#####\n#####\n ### \n",
+			),
+			(
+				RoomShape::Cross,
+				6,
+				6,
+				"  ##  \n  ##  \n######\n######\n  ##  \n  ##  \n",
+			),
+			(
+				RoomShape::LShape { notch: 0 },
+				4,
+				4,
+				"  ##\n  ##\n####\n####\n",
+			),
+			(
+				RoomShape::LShape { notch: 3 },
+				4,
+				4,
+				"####\n####\n##  \n##  \n",
+			),
+			(
+				RoomShape::Pillared,
+				5,
+				5,
+				"#####\n# # #\n#####\n# # #\n#####\n",
+			),
+			(RoomShape::Mask(mask), 4, 3, "#   \n ## \n   #\n"),
+		];
+		for (shape, width, height, expected) in cases {
+			assert_eq!(floor(&shape, width, height), expected);
+		}
+	}
+
+	#[test]
+	fn footprints_surround_the_floor() {
+		let room = |shape| Room {
+			floor: TileRectangle {
+				pos: TilePoint::new(1, 1),
+				size: TileVector::new(5, 5),
+			},
+			shape,
+			doors: None,
+		};
+
+		// A circle's footprint leaves out the corners of its bounds.
+		let circle = room(RoomShape::Circle);
+		assert!(!circle.footprint_contains(TilePoint::new(0, 0)));
+		assert!(!circle.footprint_contains(TilePoint::new(6, 6)));
+		assert!(circle.footprint_contains(TilePoint::new(1, 1)));
+		assert!(!circle.is_floor(TilePoint::new(1, 1)));
+		assert!(circle.footprint_contains(TilePoint::new(0, 3)));
+
+		// Pillars are inside the footprint but aren't floor.
+		let pillared = room(RoomShape::Pillared);
+		let pillar = TilePoint::new(2, 2);
+		assert!(pillared.footprint_contains(pillar));
+		assert!(!pillared.is_floor(pillar));
+		assert!(pillared.footprint_contains(TilePoint::new(0, 0)));
+		assert!(!pillared.footprint_contains(TilePoint::new(7, 3)));
+
+		// A cross's footprint follows its arms.
+		let cross = room(RoomShape::Cross);
+		assert!(cross.footprint_contains(TilePoint::new(3, 0)));
+		assert!(!cross.footprint_contains(TilePoint::new(0, 0)));
+	}
+}
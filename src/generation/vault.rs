@@ -0,0 +1,308 @@
+use crate::{
+	generation::{grid::Grid, Layout, Room, RoomShape},
+	geometry::{TilePoint, TileRectangle, TileVector},
+	level::{Floor, Tile},
+};
+
+/// A cell of a vault template.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Cell {
+	Wall,
+	Floor(Floor),
+	/// A wall that corridors may dig through to enter the vault.
+	Door,
+	/// A stone floor where a monster spawns.
+	MonsterSpawn,
+	/// A stone floor where an item is placed.
+	ItemSpawn,
+}
+
+impl Cell {
+	/// Whether this cell is open floor, including spawn points.
+	fn is_floor(self) -> bool {
+		matches!(self, Cell::Floor(_) | Cell::MonsterSpawn | Cell::ItemSpawn)
+	}
+}
+
+/// A hand-designed room, stamped into levels from an ASCII template.
+///
+/// Templates use `#` for walls, `.` for stone floors, `"` for grass floors,
+/// `=` for wood floors, `+` for doors, `M` for monster spawns, and `*` for item
+/// spawns. Spaces lie outside the vault and are left untouched. Doors are walls
+/// until a corridor digs through them, so a vault can only be entered through
+/// its doors.
+#[derive(Clone)]
+pub struct Vault {
+	size: TileVector,
+	/// The vault's cells in row-major order, with `None` outside the vault.
+	cells: Vec<Option<Cell>>,
+}
+
+impl Vault {
+	/// Parses a vault from `template`. Short lines are padded with spaces.
+	/// Panics if the template contains unrecognized characters, has no doors,
+	/// or has a door that isn't on a non-corner tile of its bounding edge.
+	pub fn new(template: &str) -> Self {
+		let rows = template
+			.lines()
+			.map(str::trim_end)
+			.filter(|line| !line.is_empty())
+			.map(|line| {
+				line.chars()
+					.map(|c| match c {
+						' ' => None,
+						'#' => Some(Cell::Wall),
+						'.' => Some(Cell::Floor(Floor::Stone)),
+						'"' => Some(Cell::Floor(Floor::Grass)),
+						'=' => Some(Cell::Floor(Floor::Wood)),
+						'+' => Some(Cell::Door),
+						'M' => Some(Cell::MonsterSpawn),
+						'*' => Some(Cell::ItemSpawn),
+						_ => panic!("unrecognized vault character {c:?}"),
+					})
+					.collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>();
+		let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+		let vault = Vault {
+			size: TileVector::new(width as i32, rows.len() as i32),
+			cells: rows
+				.into_iter()
+				.flat_map(|mut row| {
+					row.resize(width, None);
+					row
+				})
+				.collect(),
+		};
+
+		let doors = vault.doors(TilePoint::new(0, 0));
+		assert!(!doors.is_empty(), "vault must have a door");
+		let interior = vault.room(TilePoint::new(0, 0)).floor;
+		for door in doors {
+			let on_column = interior.pos.x <= door.x
+				&& door.x < interior.pos.x + interior.size.x;
+			let on_row = interior.pos.y <= door.y
+				&& door.y < interior.pos.y + interior.size.y;
+			assert!(
+				!interior.contains(door) && (on_column || on_row),
+				"vault doors must be on a non-corner tile of the vault's edge"
+			);
+		}
+
+		vault
+	}
+
+	/// The width and height of the vault, including its walls.
+	pub fn size(&self) -> TileVector {
+		self.size
+	}
+
+	/// A copy of this vault, mirrored horizontally if `mirrored` and then
+	/// rotated clockwise by `quarter_turns` quarter turns.
+	pub fn oriented(&self, quarter_turns: u32, mirrored: bool) -> Vault {
+		let mut vault = if mirrored {
+			self.transformed(self.size, |x, y| (self.size.x - 1 - x, y))
+		} else {
+			self.clone()
+		};
+		for _ in 0..quarter_turns % 4 {
+			let size = TileVector::new(vault.size.y, vault.size.x);
+			vault = vault.transformed(size, |x, y| (y, vault.size.y - 1 - x));
+		}
+		vault
+	}
+
+	/// The number of floor tiles in the vault, including spawn points.
+	pub fn floor_area(&self) -> i32 {
+		self.cells
+			.iter()
+			.filter(|cell| cell.is_some_and(Cell::is_floor))
+			.count() as i32
+	}
+
+	/// The room occupied by this vault with its top-left corner at `pos`. The
+	/// room's floor is the vault's floor cells, within the vault's outer edge,
+	/// and it may only be entered through the vault's doors.
+	pub fn room(&self, pos: TilePoint) -> Room {
+		let floor = TileRectangle {
+			pos,
+			size: self.size,
+		}
+		.expanded(-1);
+		let mask = TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size: floor.size,
+		};
+		let is_floor = |offset: TilePoint| {
+			let i = (offset.y + 1) * self.size.x + offset.x + 1;
+			self.cells[i as usize].is_some_and(Cell::is_floor)
+		};
+		Room {
+			floor,
+			shape: RoomShape::Mask(Grid::new(mask, is_floor)),
+			doors: Some(self.doors(pos)),
+		}
+	}
+
+	/// Stamps this vault into `layout` with its top-left corner at `pos`,
+	/// overwriting any existing terrain and recording its spawn points.
+	pub fn stamp(&self, layout: &mut Layout, pos: TilePoint) {
+		for (coords, cell) in self.cells(pos) {
+			let tile = match cell {
+				Cell::Wall | Cell::Door => Tile::Wall,
+				Cell::Floor(floor) => Tile::Floor(floor),
+				Cell::MonsterSpawn => {
+					layout.monster_spawns.push(coords);
+					Tile::Floor(Floor::Stone)
+				}
+				Cell::ItemSpawn => {
+					layout.item_spawns.push(coords);
+					Tile::Floor(Floor::Stone)
+				}
+			};
+			layout.terrain.insert(coords, tile);
+		}
+	}
+
+	/// The vault's cells with its top-left corner at `pos`, skipping those
+	/// outside the vault.
+	fn cells(
+		&self,
+		pos: TilePoint,
+	) -> impl Iterator<Item = (TilePoint, Cell)> + '_ {
+		let bounds = TileRectangle {
+			pos,
+			size: self.size,
+		};
+		bounds
+			.points()
+			.zip(&self.cells)
+			.filter_map(|(coords, cell)| cell.map(|cell| (coords, cell)))
+	}
+
+	/// The coordinates of the vault's doors with its top-left corner at `pos`.
+	fn doors(&self, pos: TilePoint) -> Vec<TilePoint> {
+		self.cells(pos)
+			.filter(|(_, cell)| *cell == Cell::Door)
+			.map(|(coords, _)| coords)
+			.collect()
+	}
+
+	/// A vault of the given `size` whose cell at (x, y) is this vault's cell at
+	/// `source(x, y)`.
+	fn transformed(
+		&self,
+		size: TileVector,
+		source: impl Fn(i32, i32) -> (i32, i32),
+	) -> Vault {
+		let bounds = TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size,
+		};
+		Vault {
+			size,
+			cells: bounds
+				.points()
+				.map(|coords| {
+					let (x, y) = source(coords.x, coords.y);
+					self.cells[(y * self.size.x + x) as usize]
+				})
+				.collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PILLARED: &str = "##+##\n#.#*#\n#M..#\n#####";
+
+	#[test]
+	fn parses_templates() {
+		let vault = Vault::new(" #+#\n #.#\n ###\n");
+		assert_eq!(vault.size(), TileVector::new(4, 3));
+		assert_eq!(vault.floor_area(), 1);
+		assert_eq!(vault.doors(TilePoint::new(0, 0)), [TilePoint::new(2, 0)]);
+		// Spaces are outside the vault.
+		assert_eq!(vault.cells(TilePoint::new(0, 0)).count(), 9);
+	}
+
+	#[test]
+	#[should_panic(expected = "unrecognized vault character")]
+	fn rejects_unrecognized_characters() {
+		Vault::new("#+#\n#x#\n###");
+	}
+
+	#[test]
+	#[should_panic(expected = "vault must have a door")]
+	fn rejects_vaults_without_doors() {
+		Vault::new("###\n#.#\n###");
+	}
+
+	#[test]
+	#[should_panic(expected = "non-corner tile")]
+	fn rejects_doors_on_corners() {
+		Vault::new("+##\n#.#\n###");
+	}
+
+	#[test]
+	#[should_panic(expected = "non-corner tile")]
+	fn rejects_doors_inside_the_vault() {
+		Vault::new("#####\n#.+.#\n#####");
+	}
+
+	#[test]
+	fn rotates_and_mirrors() {
+		let vault = Vault::new("#+##\n#..#\n####");
+		let origin = TilePoint::new(0, 0);
+
+		let rotated = vault.oriented(1, false);
+		assert_eq!(rotated.size(), TileVector::new(3, 4));
+		assert_eq!(rotated.doors(origin), [TilePoint::new(2, 1)]);
+		assert_eq!(rotated.floor_area(), vault.floor_area());
+
+		let mirrored = vault.oriented(0, true);
+		assert_eq!(mirrored.size(), vault.size());
+		assert_eq!(mirrored.doors(origin), [TilePoint::new(2, 0)]);
+
+		let mut turned = vault.clone();
+		for _ in 0..4 {
+			turned = turned.oriented(1, false);
+		}
+		assert_eq!(turned.size(), vault.size());
+		assert!(turned.cells == vault.cells);
+		assert!(vault.oriented(4, false).cells == vault.cells);
+	}
+
+	#[test]
+	fn counts_spawn_points_as_floor() {
+		assert_eq!(Vault::new(PILLARED).floor_area(), 5);
+	}
+
+	#[test]
+	fn room_covers_only_floor_cells() {
+		let room = Vault::new(PILLARED).room(TilePoint::new(10, 20));
+		assert_eq!(room.doors, Some(vec![TilePoint::new(12, 20)]));
+		assert_eq!(
+			room.floor_tiles().collect::<Vec<_>>(),
+			[
+				TilePoint::new(11, 21),
+				TilePoint::new(13, 21),
+				TilePoint::new(11, 22),
+				TilePoint::new(12, 22),
+				TilePoint::new(13, 22),
+			]
+		);
+	}
+
+	#[test]
+	fn stamps_terrain_and_spawns() {
+		let mut layout = Layout::default();
+		Vault::new(PILLARED).stamp(&mut layout, TilePoint::new(0, 0));
+		assert_eq!(layout.terrain.len(), 20);
+		assert!(matches!(layout.terrain[&TilePoint::new(2, 0)], Tile::Wall));
+		assert_eq!(layout.monster_spawns, [TilePoint::new(1, 2)]);
+		assert_eq!(layout.item_spawns, [TilePoint::new(3, 1)]);
+	}
+}
@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use rand_pcg::Pcg32;
+
+use crate::{
+	generation::{
+		grid::{tunnels, Grid},
+		make_floor, GenerationError, Layout, LevelGenerator,
+	},
+	geometry::{TilePoint, TileRectangle, TileVector, NEIGHBOR_OFFSETS_FOUR},
+	level::{Floor, Tile},
+};
+
+/// Generates levels that locally resemble a hand-drawn sample map, using the
+/// overlapping model of [Wave Function Collapse][1].
+///
+/// The sample is a rectangular ASCII map using `#` for walls, `.` for stone
+/// floors, `"` for grass floors, and `=` for wood floors.
+///
+/// [1]: https://github.com/mxgmn/WaveFunctionCollapse
+pub struct WfcGenerator {
+	/// The width and height of the sample.
+	sample_size: TileVector,
+	/// The width and height of the square patterns learned from the sample.
+	pattern_size: usize,
+	/// Open regions with fewer tiles than this are filled in.
+	min_region_size: usize,
+	/// The number of times to restart after a contradiction before giving up.
+	max_attempts: u32,
+	/// Each distinct pattern in the sample, including rotations and
+	/// reflections, as tiles in row-major order.
+	patterns: Vec<Vec<Tile>>,
+	/// How often each pattern appears in the sample.
+	weights: Vec<f64>,
+	/// For each direction in [`NEIGHBOR_OFFSETS_FOUR`] and each pattern, the
+	/// patterns that may be placed next to it in that direction.
+	propagator: Vec<Vec<Vec<usize>>>,
+}
+
+impl WfcGenerator {
+	/// Learns `pattern_size` x `pattern_size` patterns from `sample`. Panics if
+	/// the sample isn't rectangular or contains unrecognized characters. A
+	/// pattern size that doesn't fit the sample is reported by
+	/// [`LevelGenerator::validate`] instead.
+	pub fn new(
+		sample: &str,
+		pattern_size: usize,
+		min_region_size: usize,
+		max_attempts: u32,
+	) -> Self {
+		let sample = sample
+			.lines()
+			.map(|line| {
+				line.chars()
+					.map(|c| match c {
+						'#' => Tile::Wall,
+						'.' => Tile::Floor(Floor::Stone),
+						'"' => Tile::Floor(Floor::Grass),
+						'=' => Tile::Floor(Floor::Wood),
+						_ => panic!("unrecognized sample character {c:?}"),
+					})
+					.collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>();
+		let height = sample.len();
+		let width = sample.first().map_or(0, Vec::len);
+		assert!(
+			sample.iter().all(|row| row.len() == width),
+			"sample must be rectangular"
+		);
+
+		// Count every pattern in the sample, along with its rotations and
+		// reflections. There are none if the pattern size is invalid.
+		let n = pattern_size;
+		let mut patterns = Vec::new();
+		let mut weights = Vec::new();
+		let mut indices = HashMap::new();
+		let fits = 1 <= n && n <= width.min(height);
+		for y in (0..=height.saturating_sub(n)).filter(|_| fits) {
+			for x in 0..=width - n {
+				let mut pattern = (0..n * n)
+					.map(|i| sample[y + i / n][x + i % n])
+					.collect::<Vec<_>>();
+				for _ in 0..4 {
+					pattern = rotated(&pattern, n);
+					for pattern in [pattern.clone(), reflected(&pattern, n)] {
+						let index = *indices
+							.entry(pattern.clone())
+							.or_insert_with(|| {
+								patterns.push(pattern);
+								weights.push(0.0);
+								patterns.len() - 1
+							});
+						weights[index] += 1.0;
+					}
+				}
+			}
+		}
+
+		// Determine which patterns may overlap in each direction.
+		let propagator = NEIGHBOR_OFFSETS_FOUR
+			.iter()
+			.map(|offset| {
+				patterns
+					.iter()
+					.map(|p1| {
+						(0..patterns.len())
+							.filter(|&j| {
+								agree(p1, &patterns[j], n, (offset.x, offset.y))
+							})
+							.collect()
+					})
+					.collect()
+			})
+			.collect();
+
+		WfcGenerator {
+			sample_size: TileVector::new(width as i32, height as i32),
+			pattern_size,
+			min_region_size,
+			max_attempts,
+			patterns,
+			weights,
+			propagator,
+		}
+	}
+
+	/// Runs a single attempt at collapsing a `width` x `height` output,
+	/// returning the output tiles in row-major order, or `None` if a
+	/// contradiction was reached.
+	fn run(
+		&self,
+		width: usize,
+		height: usize,
+		rng: &mut Pcg32,
+	) -> Option<Vec<Tile>> {
+		let n = self.pattern_size;
+		if width < n || height < n {
+			return None;
+		}
+		let mut wave = Wave::new(self, width - n + 1, height - n + 1);
+		while let Some(cell) = wave.min_entropy_cell(rng)? {
+			wave.observe(cell, rng);
+			wave.propagate()?;
+		}
+
+		// Each output tile comes from the pattern at the nearest wave cell.
+		let output = (0..width * height)
+			.map(|i| {
+				let (x, y) = (i % width, i / width);
+				let (cx, cy) = (x.min(wave.width - 1), y.min(wave.height - 1));
+				let pattern = wave.collapsed(cy * wave.width + cx);
+				self.patterns[pattern][(y - cy) * n + x - cx]
+			})
+			.collect();
+		Some(output)
+	}
+}
+
+impl LevelGenerator for WfcGenerator {
+	fn validate(&self, tileport: TileRectangle) -> Result<(), GenerationError> {
+		let sample = self.sample_size;
+		let largest = sample.x.min(sample.y) as usize;
+		if self.pattern_size < 1 || self.pattern_size > largest {
+			return Err(GenerationError::InvalidPatternSize {
+				size: self.pattern_size,
+				sample,
+			});
+		}
+		if self.max_attempts == 0 {
+			return Err(GenerationError::NoCollapseAttempts);
+		}
+		let floor = tileport.expanded(-1);
+		let size = self.pattern_size as i32;
+		if floor.size.x < size || floor.size.y < size {
+			return Err(GenerationError::PatternTooLarge {
+				size: self.pattern_size,
+				floor: floor.size,
+			});
+		}
+		Ok(())
+	}
+
+	fn generate(
+		&self,
+		tileport: TileRectangle,
+		rng: &mut Pcg32,
+	) -> Result<Layout, GenerationError> {
+		// Leave a one-tile border around the floor for outer walls.
+		let floor = tileport.expanded(-1);
+		let (width, height) = (floor.size.x as usize, floor.size.y as usize);
+		for _ in 0..self.max_attempts {
+			let Some(output) = self.run(width, height, rng) else {
+				continue;
+			};
+			let tile_at = |coords: TilePoint| {
+				let offset = coords - floor.pos;
+				output[offset.y as usize * width + offset.x as usize]
+			};
+
+			// Fill in small open regions, and join the rest with stone tunnels
+			// so the level is connected.
+			let open = Grid::new(floor, |coords| {
+				matches!(tile_at(coords), Tile::Floor(_))
+			});
+			let mut regions = open.regions();
+			regions.retain(|region| region.len() >= self.min_region_size);
+			if regions.is_empty() {
+				continue;
+			}
+			let mut terrain = HashMap::new();
+			for coords in regions.iter().flatten() {
+				if let Tile::Floor(floor) = tile_at(*coords) {
+					make_floor(&mut terrain, *coords, floor);
+				}
+			}
+			for coords in tunnels(regions) {
+				if !matches!(terrain.get(&coords), Some(Tile::Floor(_))) {
+					make_floor(&mut terrain, coords, Floor::Stone);
+				}
+			}
+			return Ok(Layout {
+				terrain,
+				..Default::default()
+			});
+		}
+		Err(GenerationError::CollapseFailed)
+	}
+}
+
+/// The state of an in-progress collapse: which patterns are still possible in
+/// each cell of the output.
+struct Wave<'a> {
+	generator: &'a WfcGenerator,
+	width: usize,
+	height: usize,
+	/// Whether each pattern is still possible in each cell, indexed by
+	/// `cell * pattern_count + pattern`.
+	possible: Vec<bool>,
+	/// For each cell, pattern, and direction, the number of patterns still
+	/// possible in the neighboring cell opposite that direction that allow
+	/// this pattern. When any of these reaches zero, the pattern is banned.
+	support: Vec<[i32; 4]>,
+	/// The number of patterns still possible in each cell.
+	counts: Vec<usize>,
+	/// The sum of the weights of the patterns still possible in each cell.
+	weight_sums: Vec<f64>,
+	/// The sum of `weight * ln(weight)` for the patterns still possible in
+	/// each cell, used to compute entropy.
+	weight_log_weight_sums: Vec<f64>,
+	/// Banned (cell, pattern) pairs whose effects haven't been propagated.
+	pending: Vec<(usize, usize)>,
+}
+
+impl<'a> Wave<'a> {
+	fn new(generator: &'a WfcGenerator, width: usize, height: usize) -> Self {
+		let cells = width * height;
+		let pattern_count = generator.patterns.len();
+		let support = (0..pattern_count)
+			.map(|pattern| {
+				// Initially, every pattern that agrees with this one is
+				// possible in each neighbor.
+				let mut support = [0; 4];
+				for (direction, support) in support.iter_mut().enumerate() {
+					*support = generator.propagator[opposite(direction)]
+						[pattern]
+						.len() as i32;
+				}
+				support
+			})
+			.collect::<Vec<_>>();
+		let weight_sum = generator.weights.iter().sum();
+		let weight_log_weight_sum =
+			generator.weights.iter().map(|w| w * w.ln()).sum();
+		Wave {
+			generator,
+			width,
+			height,
+			possible: vec![true; cells * pattern_count],
+			support: (0..cells).flat_map(|_| support.clone()).collect(),
+			counts: vec![pattern_count; cells],
+			weight_sums: vec![weight_sum; cells],
+			weight_log_weight_sums: vec![weight_log_weight_sum; cells],
+			pending: Vec::new(),
+		}
+	}
+
+	/// The uncollapsed cell with the lowest entropy, with random
+	/// tie-breaking, or `Some(None)` if every cell has collapsed. Returns
+	/// `None` if some cell has no possible patterns.
+	fn min_entropy_cell(&self, rng: &mut Pcg32) -> Option<Option<usize>> {
+		let mut best = None;
+		let mut best_entropy = f64::INFINITY;
+		for cell in 0..self.counts.len() {
+			match self.counts[cell] {
+				0 => return None,
+				1 => continue,
+				_ => {}
+			}
+			let sum = self.weight_sums[cell];
+			let entropy = sum.ln() - self.weight_log_weight_sums[cell] / sum
+				+ rng.gen_range(0.0..1e-6);
+			if entropy < best_entropy {
+				best_entropy = entropy;
+				best = Some(cell);
+			}
+		}
+		Some(best)
+	}
+
+	/// Collapses `cell` to a single pattern, chosen randomly according to the
+	/// pattern weights.
+	fn observe(&mut self, cell: usize, rng: &mut Pcg32) {
+		let pattern_count = self.generator.patterns.len();
+		let mut roll = rng.gen_range(0.0..self.weight_sums[cell]);
+		let mut chosen = None;
+		for pattern in 0..pattern_count {
+			if self.possible[cell * pattern_count + pattern] {
+				chosen = Some(pattern);
+				roll -= self.generator.weights[pattern];
+				if roll < 0.0 {
+					break;
+				}
+			}
+		}
+		let chosen = chosen.unwrap();
+		for pattern in 0..pattern_count {
+			if pattern != chosen
+				&& self.possible[cell * pattern_count + pattern]
+			{
+				self.ban(cell, pattern);
+			}
+		}
+	}
+
+	/// Removes `pattern` from the possibilities for `cell`.
+	fn ban(&mut self, cell: usize, pattern: usize) {
+		let i = cell * self.generator.patterns.len() + pattern;
+		self.possible[i] = false;
+		self.support[i] = [0; 4];
+		let weight = self.generator.weights[pattern];
+		self.counts[cell] -= 1;
+		self.weight_sums[cell] -= weight;
+		self.weight_log_weight_sums[cell] -= weight * weight.ln();
+		self.pending.push((cell, pattern));
+	}
+
+	/// Bans patterns that are no longer supported by their neighbors until
+	/// nothing changes. Returns `None` on contradiction.
+	fn propagate(&mut self) -> Option<()> {
+		let pattern_count = self.generator.patterns.len();
+		while let Some((cell, pattern)) = self.pending.pop() {
+			let (x, y) =
+				((cell % self.width) as i32, (cell / self.width) as i32);
+			for (direction, offset) in NEIGHBOR_OFFSETS_FOUR.iter().enumerate()
+			{
+				let (x2, y2) = (x + offset.x, y + offset.y);
+				if x2 < 0
+					|| y2 < 0 || x2 as usize >= self.width
+					|| y2 as usize >= self.height
+				{
+					continue;
+				}
+				let neighbor = y2 as usize * self.width + x2 as usize;
+				let generator = self.generator;
+				for &other in &generator.propagator[direction][pattern] {
+					let i = neighbor * pattern_count + other;
+					self.support[i][direction] -= 1;
+					if self.support[i][direction] == 0 {
+						self.ban(neighbor, other);
+						if self.counts[neighbor] == 0 {
+							return None;
+						}
+					}
+				}
+			}
+		}
+		Some(())
+	}
+
+	/// The single remaining pattern in a collapsed `cell`.
+	fn collapsed(&self, cell: usize) -> usize {
+		let pattern_count = self.generator.patterns.len();
+		(0..pattern_count)
+			.find(|pattern| self.possible[cell * pattern_count + pattern])
+			.unwrap()
+	}
+}
+
+/// The index of the direction opposite `direction` in
+/// [`NEIGHBOR_OFFSETS_FOUR`].
+fn opposite(direction: usize) -> usize {
+	direction ^ 1
+}
+
+/// `pattern` rotated a quarter turn.
+fn rotated(pattern: &[Tile], n: usize) -> Vec<Tile> {
+	(0..n * n)
+		.map(|i| {
+			let (x, y) = (i % n, i / n);
+			pattern[x * n + n - 1 - y]
+		})
+		.collect()
+}
+
+/// `pattern` mirrored horizontally.
+fn reflected(pattern: &[Tile], n: usize) -> Vec<Tile> {
+	(0..n * n)
+		.map(|i| {
+			let (x, y) = (i % n, i / n);
+			pattern[y * n + n - 1 - x]
+		})
+		.collect()
+}
+
+/// Whether `p2`, offset from `p1` by `(dx, dy)`, agrees with `p1` wherever the
+/// two patterns overlap.
+fn agree(p1: &[Tile], p2: &[Tile], n: usize, (dx, dy): (i32, i32)) -> bool {
+	let n = n as i32;
+	let (x_min, x_max) = (dx.max(0), (n + dx).min(n));
+	let (y_min, y_max) = (dy.max(0), (n + dy).min(n));
+	(y_min..y_max).all(|y| {
+		(x_min..x_max).all(|x| {
+			p1[(y * n + x) as usize] == p2[((y - dy) * n + x - dx) as usize]
+		})
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SAMPLE: &str = "#####\n#...#\n#.#.#\n#...#\n#####";
+
+	fn tileport() -> TileRectangle {
+		TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size: TileVector::new(20, 20),
+		}
+	}
+
+	#[test]
+	fn accepts_patterns_that_fit_the_sample() {
+		for size in 1..=5 {
+			let generator = WfcGenerator::new(SAMPLE, size, 1, 10);
+			assert!(generator.validate(tileport()).is_ok());
+		}
+	}
+
+	#[test]
+	fn rejects_patterns_that_dont_fit_the_sample() {
+		for size in [0, 6, 30] {
+			let generator = WfcGenerator::new(SAMPLE, size, 1, 10);
+			assert!(matches!(
+				generator.validate(tileport()),
+				Err(GenerationError::InvalidPatternSize { .. })
+			));
+		}
+	}
+
+	#[test]
+	fn rejects_no_attempts() {
+		let generator = WfcGenerator::new(SAMPLE, 3, 1, 0);
+		assert!(matches!(
+			generator.validate(tileport()),
+			Err(GenerationError::NoCollapseAttempts)
+		));
+	}
+
+	#[test]
+	fn rejects_patterns_larger_than_the_floor() {
+		let generator = WfcGenerator::new(SAMPLE, 5, 1, 10);
+		let tileport = TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size: TileVector::new(6, 20),
+		};
+		assert!(matches!(
+			generator.validate(tileport),
+			Err(GenerationError::PatternTooLarge { .. })
+		));
+	}
+}
@@ -166,7 +166,7 @@ pub struct Point<T> {
 }
 
 impl<T> Point<T> {
-	pub fn new(x: T, y: T) -> Self {
+	pub const fn new(x: T, y: T) -> Self {
 		Point { x, y }
 	}
 }
@@ -312,6 +312,31 @@ impl<T> Rectangle<T> {
 		start_x <= end_x && start_y <= end_y
 	}
 
+	/// Whether `point` lies within `self`.
+	pub fn contains(self, point: Point<T>) -> bool
+	where
+		T: Copy + Ord + Add<Output = T>,
+	{
+		self.pos.x <= point.x
+			&& point.x < self.pos.x + self.size.x
+			&& self.pos.y <= point.y
+			&& point.y < self.pos.y + self.size.y
+	}
+
+	/// `self` grown by `amount` on every side.
+	pub fn expanded(self, amount: T) -> Self
+	where
+		T: Copy + Add<Output = T> + Sub<Output = T>,
+	{
+		Rectangle {
+			pos: Point::new(self.pos.x - amount, self.pos.y - amount),
+			size: Vector::new(
+				self.size.x + amount + amount,
+				self.size.y + amount + amount,
+			),
+		}
+	}
+
 	/// The rectangle's width times height.
 	pub fn area(self) -> T
 	where
@@ -320,3 +345,13 @@ impl<T> Rectangle<T> {
 		self.size.x * self.size.y
 	}
 }
+
+impl TileRectangle {
+	/// The coordinates of every tile within `self`, in row-major order.
+	pub fn points(self) -> impl Iterator<Item = TilePoint> {
+		(self.pos.y..self.pos.y + self.size.y).flat_map(move |y| {
+			(self.pos.x..self.pos.x + self.size.x)
+				.map(move |x| TilePoint::new(x, y))
+		})
+	}
+}
@@ -0,0 +1,164 @@
+use std::rc::Rc;
+
+use ggez::{
+	graphics::{Canvas, Color, DrawParam, Drawable, Text, TextFragment},
+	Context,
+};
+
+use crate::{
+	creature::Creature,
+	geometry::{ScreenPoint, ScreenRectangle, ScreenVector},
+	level::{capitalized, Level},
+	meshes::Meshes,
+	shared::Shared,
+};
+
+/// The width of the sidebar at the right edge of the screen.
+const WIDTH: f32 = 300.0;
+
+/// Space between the edges of the sidebar and its contents.
+const PADDING: f32 = 16.0;
+
+/// The height of a line of text in the sidebar.
+const LINE_HEIGHT: f32 = 24.0;
+
+/// The height of a health bar.
+const BAR_HEIGHT: f32 = 8.0;
+
+/// Splits `screen` into the region the level is shown in and, to its right,
+/// the region reserved for the heads-up display.
+pub fn split(screen: ScreenRectangle) -> (ScreenRectangle, ScreenRectangle) {
+	let width = WIDTH.min(screen.size.x);
+	let level = ScreenRectangle {
+		pos: screen.pos,
+		size: ScreenVector::new(screen.size.x - width, screen.size.y),
+	};
+	let hud = ScreenRectangle {
+		pos: ScreenPoint::new(screen.pos.x + level.size.x, screen.pos.y),
+		size: ScreenVector::new(width, screen.size.y),
+	};
+	(level, hud)
+}
+
+/// Draws a sidebar in `bounds` showing the player's stats, the level's depth
+/// and turn count, the description of the room the player is in, if any, and
+/// the creatures the player can see.
+pub fn draw(
+	ctx: &Context,
+	canvas: &mut Canvas,
+	meshes: &Meshes,
+	bounds: ScreenRectangle,
+	player: &Shared<Creature>,
+	level: &Level,
+	room_feeling: Option<&str>,
+) {
+	canvas.draw(
+		&meshes.panel,
+		DrawParam::new()
+			.dest(bounds.pos)
+			.scale(bounds.size)
+			.color(Color::from_rgb(24, 24, 24)),
+	);
+	let mut column = Column {
+		canvas,
+		meshes,
+		pos: bounds.pos + ScreenVector::new(PADDING, PADDING),
+		width: bounds.size.x - 2.0 * PADDING,
+	};
+
+	let (origin, health, max_health, strength) = {
+		let player = player.borrow();
+		(
+			player.coords,
+			player.stats.health(),
+			player.max_health(),
+			player.stats.strength,
+		)
+	};
+	column.text(format!("Health: {health}/{max_health}"));
+	column.health_bar(health, max_health);
+	column.text(format!("Strength: {strength}"));
+	column.text(format!("Depth: {}", level.depth()));
+	column.text(format!("Turn: {}", level.turn()));
+	column.pos.y += LINE_HEIGHT;
+	if let Some(feeling) = room_feeling {
+		column.paragraph(ctx, feeling);
+		column.pos.y += LINE_HEIGHT;
+	}
+
+	column.text("In view:".to_string());
+	// List the nearest creatures first, in a stable order.
+	let mut creatures = level
+		.visible_creatures()
+		.filter(|creature| !Rc::ptr_eq(creature, player))
+		.map(|creature| creature.borrow())
+		.collect::<Vec<_>>();
+	creatures.sort_by_key(|creature| {
+		let offset = creature.coords - origin;
+		let distance = offset.x.abs() + offset.y.abs();
+		(distance, creature.coords.y, creature.coords.x)
+	});
+	if creatures.is_empty() {
+		column.text("Nothing".to_string());
+	}
+	for creature in creatures {
+		// Stop once the sidebar is full.
+		if column.pos.y + LINE_HEIGHT + BAR_HEIGHT
+			> bounds.pos.y + bounds.size.y
+		{
+			break;
+		}
+		let appearance = creature.species.appearance();
+		let mut text = Text::new(
+			TextFragment::new(appearance.glyph).color(appearance.color),
+		);
+		text.add(format!(" {}", capitalized(creature.species.name())));
+		column.canvas.draw(&text, DrawParam::new().dest(column.pos));
+		column.pos.y += LINE_HEIGHT;
+		column.health_bar(creature.stats.health(), creature.max_health());
+	}
+}
+
+/// Lays out the contents of the sidebar from top to bottom.
+struct Column<'a> {
+	canvas: &'a mut Canvas,
+	meshes: &'a Meshes,
+	/// Where the next line starts.
+	pos: ScreenPoint,
+	width: f32,
+}
+
+impl Column<'_> {
+	fn text(&mut self, text: String) {
+		self.canvas
+			.draw(&Text::new(text), DrawParam::new().dest(self.pos));
+		self.pos.y += LINE_HEIGHT;
+	}
+
+	/// Draws `text`, wrapped to the width of the column.
+	fn paragraph(&mut self, ctx: &Context, text: &str) {
+		let mut text = Text::new(text);
+		text.set_bounds(ScreenVector::new(self.width, f32::INFINITY));
+		self.canvas.draw(&text, DrawParam::new().dest(self.pos));
+		let height = text.dimensions(ctx).map_or(LINE_HEIGHT, |rect| rect.h);
+		self.pos.y += height.max(LINE_HEIGHT);
+	}
+
+	/// Draws a bar filled in proportion to `health` out of `max_health`.
+	fn health_bar(&mut self, health: u32, max_health: u32) {
+		let fraction = health as f32 / max_health.max(1) as f32;
+		for (width, color) in [
+			(self.width, Color::from_rgb(96, 0, 0)),
+			(self.width * fraction.min(1.0), Color::from_rgb(0, 192, 0)),
+		] {
+			self.canvas.draw(
+				&self.meshes.panel,
+				DrawParam::new()
+					.dest(self.pos)
+					.scale(ScreenVector::new(width, BAR_HEIGHT))
+					.color(color),
+			);
+		}
+		self.pos.y += BAR_HEIGHT + PADDING / 2.0;
+	}
+}
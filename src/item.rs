@@ -1,26 +1,42 @@
-use ggez::graphics::{Canvas, DrawParam};
+use crate::geometry::TilePoint;
 
-use crate::{geometry::TilePoint, level::TileLayout, meshes::Meshes};
+/// A type of [`Item`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum ItemKind {
+	Gold,
+	Potion,
+	Scroll,
+	Weapon,
+}
+
+impl ItemKind {
+	/// Every kind of item.
+	pub const ALL: [ItemKind; 4] = [
+		ItemKind::Gold,
+		ItemKind::Potion,
+		ItemKind::Scroll,
+		ItemKind::Weapon,
+	];
+
+	/// A short name for an item of this kind, with an article.
+	pub fn name(&self) -> &'static str {
+		match self {
+			ItemKind::Gold => "some gold",
+			ItemKind::Potion => "a potion",
+			ItemKind::Scroll => "a scroll",
+			ItemKind::Weapon => "a weapon",
+		}
+	}
+}
 
 #[derive(Debug)]
 pub struct Item {
-	coords: TilePoint,
+	pub kind: ItemKind,
+	pub coords: TilePoint,
 }
 
 impl Item {
-	pub fn draw(
-		&self,
-		canvas: &mut Canvas,
-		meshes: &Meshes,
-		tile_layout: &TileLayout,
-	) {
-		let screen_tile = tile_layout.to_screen(self.coords);
-		let mesh = &meshes.item;
-		canvas.draw(
-			mesh,
-			DrawParam::new()
-				.dest(screen_tile.pos + screen_tile.size / 2.0)
-				.scale(screen_tile.size),
-		);
+	pub fn new(kind: ItemKind, coords: TilePoint) -> Item {
+		Item { kind, coords }
 	}
 }
@@ -0,0 +1,39 @@
+use std::ops::RangeInclusive;
+
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg32;
+
+use crate::item::ItemKind;
+
+/// A kind of item that may be placed from an [`ItemTable`].
+pub struct ItemEntry {
+	pub kind: ItemKind,
+	/// The relative likelihood of choosing this entry over others.
+	pub weight: u32,
+	/// The level depths at which this entry may be chosen.
+	pub depths: RangeInclusive<u32>,
+}
+
+/// Describes which items are scattered through a level and where.
+pub struct ItemTable {
+	pub entries: Vec<ItemEntry>,
+	/// How many times likelier an item is to land on a given tile in a dead
+	/// end or vault than on any other open tile.
+	pub treasure_bias: u32,
+}
+
+impl ItemTable {
+	/// Chooses an item kind available at `depth` at random, using the entries'
+	/// weights, or `None` if no entries are available.
+	pub fn choose(&self, depth: u32, rng: &mut Pcg32) -> Option<ItemKind> {
+		let available = self
+			.entries
+			.iter()
+			.filter(|entry| entry.depths.contains(&depth))
+			.collect::<Vec<_>>();
+		available
+			.choose_weighted(rng, |entry| entry.weight)
+			.ok()
+			.map(|entry| entry.kind)
+	}
+}
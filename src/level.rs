@@ -1,22 +1,31 @@
-use std::collections::{HashMap, HashSet};
+mod loader;
+
+use std::{
+	cmp::Reverse,
+	collections::{HashMap, HashSet},
+};
 
-use ggez::graphics::{Canvas, Color, DrawParam};
 use rand::seq::SliceRandom;
-use rand::Rng;
 use rand_pcg::Pcg32;
 
 use crate::{
+	analysis::{LevelAnalysis, LevelRequirements},
+	camera::Camera,
 	creature::{Behavior, Creature, Faction, Species},
 	dijkstra_map::DijkstraMap,
-	disjoint_sets::DisjointSets,
+	generation::{
+		check_ratio, GenerationError, GenerationStats, LevelGenerator,
+		RoomGraph, RoomRole,
+	},
 	geometry::{
-		random_neighbor_offset_eight, RectangleIntersection, ScreenPoint,
-		ScreenRectangle, ScreenVector, TileIntersection, TilePoint,
-		TileRectangle, TileVector,
+		ScreenPoint, ScreenRectangle, ScreenVector, TilePoint, TileRectangle,
+		TileVector, TILE_DOWN, TILE_LEFT, TILE_RIGHT, TILE_UP,
 	},
 	item::Item,
-	meshes::Meshes,
+	item_table::ItemTable,
+	renderer::{Perception, Pose, Renderer, TextRenderer, WallMask},
 	shared::{share, Shared},
+	spawn_table::SpawnTable,
 	vision,
 };
 
@@ -33,7 +42,10 @@ pub struct TileLayout {
 }
 
 impl TileLayout {
-	fn new(viewport: ScreenRectangle, tileport: TileRectangle) -> TileLayout {
+	pub fn new(
+		viewport: ScreenRectangle,
+		tileport: TileRectangle,
+	) -> TileLayout {
 		// Shrink the viewport as needed so that its aspect ratio matches the
 		// tileport's.
 		let tileport_ar = tileport.size.x as f32 / tileport.size.y as f32;
@@ -70,6 +82,17 @@ impl TileLayout {
 		}
 	}
 
+	/// The tiles this layout maps to the screen.
+	pub fn tileport(&self) -> TileRectangle {
+		self.tileport
+	}
+
+	/// The on-screen width and height of a tile, including the gap between
+	/// neighboring tiles.
+	pub fn tile_size(&self) -> ScreenVector {
+		self.tile_size
+	}
+
 	pub fn to_screen(&self, coords: TilePoint) -> ScreenRectangle {
 		let pos = ScreenPoint::new(
 			self.viewport.pos.x
@@ -82,53 +105,56 @@ impl TileLayout {
 			size: self.tile_size - ScreenVector::new(1.0, 1.0),
 		}
 	}
-}
 
-enum Perception {
-	Seen,
-	Remembered,
+	/// The coordinates of the tile shown at `point` on screen, or `None` if
+	/// there's no tile there.
+	pub fn to_tile(&self, point: ScreenPoint) -> Option<TilePoint> {
+		let offset = point - self.viewport.pos;
+		let size = self.viewport.size;
+		if offset.x < 0.0
+			|| offset.y < 0.0
+			|| offset.x >= size.x
+			|| offset.y >= size.y
+		{
+			return None;
+		}
+		Some(TilePoint::new(
+			self.tileport.pos.x + (offset.x / self.tile_size.x) as i32,
+			self.tileport.pos.y + (offset.y / self.tile_size.y) as i32,
+		))
+	}
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Floor {
 	Stone,
 	Grass,
 	Wood,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Tile {
 	Floor(Floor),
 	Wall,
 }
 
 impl Tile {
-	fn draw(
-		&self,
-		canvas: &mut Canvas,
-		meshes: &Meshes,
-		tile_layout: &TileLayout,
-		coords: TilePoint,
-		perception: Perception,
-	) {
-		let color = match perception {
-			Perception::Seen => Color::WHITE,
-			Perception::Remembered => Color::from_rgba(255, 255, 255, 64),
-		};
-		let screen_tile = tile_layout.to_screen(coords);
-		let mesh = match self {
-			Tile::Floor(Floor::Stone) => &meshes.stone_floor,
-			Tile::Floor(Floor::Grass) => &meshes.grass_floor,
-			Tile::Floor(Floor::Wood) => &meshes.wood_floor,
-			Tile::Wall => &meshes.wall,
-		};
-		canvas.draw(
-			mesh,
-			DrawParam::new()
-				.dest(screen_tile.pos)
-				.scale(screen_tile.size)
-				.color(color),
-		);
+	/// Every kind of tile.
+	pub const ALL: [Tile; 4] = [
+		Tile::Wall,
+		Tile::Floor(Floor::Stone),
+		Tile::Floor(Floor::Grass),
+		Tile::Floor(Floor::Wood),
+	];
+
+	/// A short name for this kind of tile.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Tile::Floor(Floor::Stone) => "stone floor",
+			Tile::Floor(Floor::Grass) => "grass",
+			Tile::Floor(Floor::Wood) => "wooden floor",
+			Tile::Wall => "wall",
+		}
 	}
 }
 
@@ -143,10 +169,12 @@ pub enum Collision {
 #[derive(Default)]
 pub struct DijkstraMaps {
 	pub enemies: HashMap<Faction, DijkstraMap>,
+	/// A map to each room in the level's room graph, by room index.
+	pub rooms: Vec<DijkstraMap>,
 }
 
 pub struct Level {
-	tile_layout: TileLayout,
+	camera: Camera,
 	terrain: HashMap<TilePoint, Tile>,
 	creatures: HashMap<TilePoint, Shared<Creature>>,
 	items: HashMap<TilePoint, Shared<Item>>,
@@ -155,6 +183,15 @@ pub struct Level {
 	/// Tiles the player remembers seeing.
 	memory: HashMap<TilePoint, Tile>,
 	dijkstra_maps: DijkstraMaps,
+	room_graph: RoomGraph,
+	/// Where the player starts.
+	start: TilePoint,
+	generation_stats: GenerationStats,
+	analysis: LevelAnalysis,
+	/// How far below the surface the level is.
+	depth: u32,
+	/// The number of turns that have passed in the level.
+	turn: u32,
 }
 
 /// Configuration settings for level generation.
@@ -163,254 +200,278 @@ pub struct GenerationConfig {
 	pub viewport: ScreenRectangle,
 	/// The region in tile space the level should cover.
 	pub tileport: TileRectangle,
-	/// The minimum allowable proportion of all tiles within `tileport` to be
-	/// marked as floors. Additional rooms will be added until this proportion
-	/// is reached (up to a retry limit, in case additional rooms can't fit).
-	pub min_floor_ratio: f32,
-	/// Minimum width of a room's floor.
-	pub min_room_size: i32,
-	/// Maximum length of a room's floor.
-	pub max_room_size: i32,
+	/// The preferred on-screen width and height of a tile. Levels too large to
+	/// fit in the viewport at this size scroll to follow the player.
+	pub tile_size: f32,
+	/// The algorithm used to lay out the level's terrain.
+	pub generator: Box<dyn LevelGenerator>,
+	/// How far below the surface the level is. Deeper levels may spawn
+	/// different creatures.
+	pub depth: u32,
+	/// The creatures that populate the level.
+	pub spawn_table: SpawnTable,
+	/// The items scattered through the level.
+	pub item_table: ItemTable,
+	/// The number of items to scatter through the level, in addition to those
+	/// placed by vaults.
+	pub item_budget: usize,
+	/// Thresholds a generated level must meet to be accepted.
+	pub requirements: LevelRequirements,
+	/// The number of times to try generating the level before giving up, in
+	/// case randomness produces an unusable level.
+	pub max_attempts: u32,
 }
 
-struct Room {
-	floor: TileRectangle,
+impl GenerationConfig {
+	/// Checks that the config can be used to generate a level.
+	pub fn validate(&self) -> Result<(), GenerationError> {
+		let size = self.tileport.size;
+		if size.x < 3 || size.y < 3 {
+			return Err(GenerationError::TileportTooSmall(size));
+		}
+		if self.tile_size <= 0.0 {
+			return Err(GenerationError::InvalidTileSize(self.tile_size));
+		}
+		if self.max_attempts == 0 {
+			return Err(GenerationError::NoAttempts);
+		}
+		self.generator.validate(self.tileport)?;
+		check_ratio("min_floor_ratio", self.requirements.min_floor_ratio)?;
+		self.spawn_table.validate()
+	}
 }
 
-const MAX_ROOM_PLACEMENT_RETRIES: u32 = 100;
-
 impl Level {
-	pub fn generate(config: GenerationConfig, rng: &mut Pcg32) -> Level {
-		// Leave a one-tile border around the floor for outer walls.
-		let floor = TileRectangle {
-			pos: TilePoint {
-				x: config.tileport.pos.x + 1,
-				y: config.tileport.pos.y + 1,
-			},
-			size: TileVector::new(
-				config.tileport.size.x - 2,
-				config.tileport.size.y - 2,
-			),
-		};
-
-		let mut rooms: Vec<Room> = Vec::new();
-
-		// Add rooms until the target floor coverage is reached.
-		let total_area = floor.area();
-		let mut floor_area = 0;
-		let mut retries = 0;
-		while (floor_area as f32 / total_area as f32) < config.min_floor_ratio {
-			let mut new_room = {
-				let size = TileVector::new(
-					rng.gen_range(config.min_room_size..=config.max_room_size),
-					rng.gen_range(config.min_room_size..=config.max_room_size),
-				);
-				Room {
-					floor: TileRectangle {
-						pos: TilePoint::new(
-							floor.pos.x
-								+ rng.gen_range(0..=floor.size.x - size.x),
-							floor.pos.y
-								+ rng.gen_range(0..=floor.size.y - size.y),
-						),
-						size,
-					},
-				}
-			};
-
-			// Nudge the room while it touches any existing rooms. (This uses an
-			// inefficient O(n^2) collision algorithm, but it should be good
-			// enough for the number of rooms we're dealing with.)
-			let nudge = random_neighbor_offset_eight(rng);
-			let mut nudging = true;
-			while nudging {
-				nudging = false;
-				for room in rooms.iter() {
-					if new_room.floor.touching(room.floor) {
-						nudging = true;
-						new_room.floor.pos += nudge;
-					}
-				}
+	/// Generates a level using `config`, retrying up to the config's attempt
+	/// limit if a generated level is unusable or fails the config's
+	/// requirements.
+	pub fn generate(
+		config: GenerationConfig,
+		rng: &mut Pcg32,
+	) -> Result<Level, GenerationError> {
+		config.validate()?;
+		let mut last_error = GenerationError::NoAttempts;
+		for _ in 0..config.max_attempts {
+			match Level::try_generate(&config, rng) {
+				Ok(level) => return Ok(level),
+				Err(error) => last_error = error,
 			}
-
-			// Crop the room to fit within the tileport.
-			if let RectangleIntersection::Real(intersection) =
-				new_room.floor.intersection(floor)
-			{
-				new_room.floor = intersection;
-			} else {
-				// If the room is completely outside the level, set its size to
-				// zero so it will be discarded.
-				new_room.floor.size = TileVector::new(0, 0);
-			}
-
-			// If the room is now too small, discard it and try again.
-			if new_room.floor.size.x < config.min_room_size
-				|| new_room.floor.size.y < config.min_room_size
-			{
-				retries += 1;
-				if retries > MAX_ROOM_PLACEMENT_RETRIES {
-					break;
-				} else {
-					continue;
-				}
-			}
-
-			retries = 0;
-			floor_area += new_room.floor.area();
-			rooms.push(new_room);
 		}
+		Err(GenerationError::AttemptsExhausted {
+			attempts: config.max_attempts,
+			last: Box::new(last_error),
+		})
+	}
 
-		let mut terrain = HashMap::new();
-		let make_floor = |terrain: &mut HashMap<TilePoint, Tile>,
-		                  coords: TilePoint,
-		                  floor: Floor| {
-			for x in coords.x - 1..=coords.x + 1 {
-				for y in coords.y - 1..=coords.y + 1 {
-					if x == coords.x && y == coords.y {
-						terrain.insert(coords, Tile::Floor(floor));
-					} else {
-						terrain
-							.entry(TilePoint::new(x, y))
-							.or_insert(Tile::Wall);
-					}
-				}
-			}
+	/// A level with the given terrain and rooms and no creatures or items.
+	fn new(
+		camera: Camera,
+		terrain: HashMap<TilePoint, Tile>,
+		room_graph: RoomGraph,
+		start: TilePoint,
+		generation_stats: GenerationStats,
+		analysis: LevelAnalysis,
+		depth: u32,
+	) -> Level {
+		let mut level = Level {
+			camera,
+			terrain,
+			creatures: HashMap::new(),
+			items: HashMap::new(),
+			vision: HashSet::new(),
+			memory: HashMap::new(),
+			dijkstra_maps: DijkstraMaps::default(),
+			room_graph,
+			start,
+			generation_stats,
+			analysis,
+			depth,
+			turn: 0,
 		};
+		// Terrain doesn't change, so the maps to each room only need to be
+		// built once.
+		level.dijkstra_maps.rooms = level
+			.room_graph
+			.rooms
+			.iter()
+			.map(|node| level.terrain_map(|coords| node.room.is_floor(*coords)))
+			.collect();
+		level
+	}
 
-		// Open the floor of each room.
-		for room in rooms.iter() {
-			let floor = if rng.gen() { Floor::Wood } else { Floor::Grass };
-			for x in room.floor.pos.x..room.floor.pos.x + room.floor.size.x {
-				for y in room.floor.pos.y..room.floor.pos.y + room.floor.size.y
-				{
-					make_floor(&mut terrain, TilePoint::new(x, y), floor);
-				}
-			}
-		}
-
-		// Build a forest of disjoint sets of connected rooms. Initially, each
-		// room is in its own singleton set.
-		let mut connected_rooms = DisjointSets::new(rooms.len());
+	/// Makes a single attempt at generating a level using `config`, which must
+	/// be valid.
+	fn try_generate(
+		config: &GenerationConfig,
+		rng: &mut Pcg32,
+	) -> Result<Level, GenerationError> {
+		let layout = config.generator.generate(config.tileport, rng)?;
+
+		// Start the player anywhere in the entrance room if there is one, or
+		// else anywhere open, away from any monster spawns.
+		let open_coords = layout
+			.terrain
+			.iter()
+			.filter(|(_, tile)| matches!(tile, Tile::Floor(_)))
+			.map(|(coords, _)| *coords)
+			.collect::<Vec<_>>();
+		// Sort the candidates so that the start depends only on the seed.
+		let start_candidates = |coords: Vec<TilePoint>| {
+			let mut candidates = coords
+				.into_iter()
+				.filter(|coords| !layout.monster_spawns.contains(coords))
+				.collect::<Vec<_>>();
+			candidates.sort_by_key(|coords| (coords.y, coords.x));
+			candidates
+		};
+		let entrance_coords = start_candidates(
+			layout
+				.rooms
+				.find(RoomRole::Entrance)
+				.map(|i| layout.rooms.rooms[i].room.floor_tiles().collect())
+				.unwrap_or_default(),
+		);
+		let open_start_coords = start_candidates(open_coords.clone());
+		let start = *entrance_coords
+			.choose(rng)
+			.or_else(|| open_start_coords.choose(rng))
+			.ok_or(GenerationError::NoOpenTiles)?;
+
+		// Reject levels that don't meet the requirements before doing any more
+		// work on them.
+		let analysis =
+			LevelAnalysis::new(&layout.terrain, config.tileport, start);
+		analysis.check(&config.requirements, &layout.stats)?;
+
+		let mut level = Level::new(
+			Camera::new(config.viewport, config.tileport, config.tile_size),
+			layout.terrain,
+			layout.rooms,
+			start,
+			layout.stats,
+			analysis,
+			config.depth,
+		);
 
-		// Build an ordered queue of distances between rooms.
-		struct Edge {
-			i: usize,
-			j: usize,
-			intersection: TileIntersection,
+		// Place items and creatures at the layout's spawn points.
+		for coords in layout.item_spawns {
+			if let Some(kind) = config.item_table.choose(config.depth, rng) {
+				level.items.insert(coords, share(Item::new(kind, coords)));
+			}
 		}
-		let mut edges = Vec::new();
-		for (i, room1) in rooms.iter().enumerate() {
-			for (j, room2) in rooms.iter().enumerate().skip(i) {
-				edges.push(Edge {
-					i,
-					j,
-					intersection: room1.floor.intersection(room2.floor),
-				});
+		let table = &config.spawn_table;
+		for coords in layout.monster_spawns {
+			if let Some(entry) = table.choose(config.depth, rng) {
+				level.spawn_enemy(entry.species, coords);
 			}
 		}
-		edges.sort_by(|e1, e2| {
-			e2.intersection.distance().cmp(&e1.intersection.distance())
-		});
 
-		// Connect the closest two rooms until all rooms are connected.
-		while let Some(Edge { i, j, intersection }) = edges.pop() {
-			// Connect the rooms.
-			let waypoints = match intersection {
-				RectangleIntersection::Real(_) => {
-					// At this point all rooms should be nonintersecting, so
-					// this should be unreachable, but there wouldn't be any
-					// work to do anyway.
-					vec![]
-				}
-				RectangleIntersection::Horizontal(rect) => {
-					// Connect vertically.
-					let x = rng.gen_range(rect.pos.x..rect.pos.x + rect.size.x);
-					vec![
-						TilePoint::new(x, rect.pos.y),
-						TilePoint::new(x, rect.pos.y + rect.size.y - 1),
-					]
-				}
-				RectangleIntersection::Vertical(rect) => {
-					// Connect horizontally.
-					let y = rng.gen_range(rect.pos.y..rect.pos.y + rect.size.y);
-					vec![
-						TilePoint::new(rect.pos.x, y),
-						TilePoint::new(rect.pos.x + rect.size.x - 1, y),
-					]
-				}
-				RectangleIntersection::None(rect) => {
-					// Expand the rectangle one tile up and left so that [pos,
-					// pos + size] includes the rooms' corners.
-					let rect = TileRectangle {
-						pos: rect.pos - TileVector::new(1, 1),
-						size: rect.size + TileVector::new(1, 1),
-					};
-					// Determine if the rooms are diagonal "/" or "\".
-					let (room1, room2) = (&rooms[i], &rooms[j]);
-					let (p1, p3) = if (room1.floor.pos.x <= room2.floor.pos.x)
-						== (room1.floor.pos.y <= room2.floor.pos.y)
-					{
-						(rect.pos, rect.pos + rect.size)
-					} else {
-						let (mut p1, mut p3) = (rect.pos, rect.pos + rect.size);
-						(p1.y, p3.y) = (p3.y, p1.y);
-						(p1, p3)
-					};
-					// Connect the start and end via a random elbow.
-					let p2 = if rng.gen() {
-						TilePoint::new(p1.x, p3.y)
-					} else {
-						TilePoint::new(p3.x, p1.y)
-					};
-					vec![p1, p2, p3]
-				}
+		// Spawn groups of creatures from the spawn table, away from the
+		// player's start.
+		let start_room = level.room_graph.room_at(start);
+		let start_map = level.terrain_map(|coords| *coords == start);
+		let near_start = |coords: &TilePoint| {
+			let in_start_room = start_room.is_some_and(|i| {
+				level.room_graph.rooms[i].room.is_floor(*coords)
+			});
+			let in_safe_radius = start_map
+				.distance(*coords)
+				.is_some_and(|distance| distance as usize <= table.safe_radius);
+			// Never spawn on the start itself, even with a safe radius of 0.
+			*coords == start || in_start_room || in_safe_radius
+		};
+		let mut candidates = level
+			.unoccupied_coords()
+			.into_iter()
+			.filter(|coords| !near_start(coords))
+			.collect::<Vec<_>>();
+		let mut remaining =
+			(table.density * open_coords.len() as f32).round() as usize;
+		while remaining > 0 {
+			let Some(entry) = table.choose(config.depth, rng) else {
+				break;
 			};
-			for waypoints in waypoints.windows(2) {
-				for coords in vision::line_between(waypoints[0], waypoints[1]) {
-					make_floor(&mut terrain, coords, Floor::Stone);
-				}
-			}
-
-			// Merge the two connection sets. Stop if all rooms are connected
-			// and the most recently merged rooms were far enough apart.
-			if connected_rooms.merge(i, j) == rooms.len()
-				&& intersection.distance() > 2
-			{
+			candidates.shuffle(rng);
+			let Some(leader) = candidates.pop() else {
 				break;
+			};
+			level.spawn_enemy(entry.species, leader);
+			remaining -= 1;
+
+			// Gather the rest of the group as close to the leader as possible,
+			// by walking distance.
+			let leader_map = level.terrain_map(|coords| *coords == leader);
+			candidates.sort_by_key(|coords| {
+				Reverse(leader_map.distance(*coords).unwrap_or(isize::MAX))
+			});
+			for _ in 1..entry.roll_group_size(rng) {
+				let Some(coords) = candidates.pop().filter(|_| remaining > 0)
+				else {
+					break;
+				};
+				level.spawn_enemy(entry.species, coords);
+				remaining -= 1;
 			}
 		}
 
-		let mut level = Level {
-			tile_layout: TileLayout::new(config.viewport, config.tileport),
-			terrain,
-			creatures: HashMap::new(),
-			items: HashMap::new(),
-			vision: HashSet::new(),
-			memory: HashMap::new(),
-			dijkstra_maps: DijkstraMaps::default(),
-		};
-
-		// Spawn creatures.
-		let mut unoccupied_coords = level.unoccupied_coords();
-		unoccupied_coords.shuffle(rng);
-		// TODO: Configure spawning in GenerationConfig.
-		for coords in unoccupied_coords.into_iter().take(10) {
-			let species = if rng.gen_range(0.0..1.0) < 0.15 {
-				Species::Ogre
-			} else {
-				Species::Goblin
+		// Scatter items from the item table, favoring dead ends and vaults.
+		let item_table = &config.item_table;
+		let mut item_candidates = open_coords
+			.iter()
+			.copied()
+			.filter(|coords| !level.items.contains_key(coords))
+			.map(|coords| {
+				let is_treasure_room =
+					level.room_graph.room_at(coords).is_some_and(|i| {
+						matches!(
+							level.room_graph.rooms[i].role,
+							RoomRole::DeadEnd | RoomRole::Vault
+						)
+					});
+				let weight = if is_treasure_room {
+					item_table.treasure_bias
+				} else {
+					1
+				};
+				(coords, weight)
+			})
+			.collect::<Vec<_>>();
+		// Choose from a fixed order so that placement depends only on the seed.
+		item_candidates.sort_by_key(|(coords, _)| (coords.y, coords.x));
+		for _ in 0..config.item_budget {
+			let Some(kind) = item_table.choose(config.depth, rng) else {
+				break;
 			};
-			// Ignore failure to spawn.
-			let _ = level.spawn(share(Creature::new(
-				Faction::Enemy,
-				species,
-				Behavior::Patrolling,
-				coords,
-			)));
+			let Ok(&(coords, _)) =
+				item_candidates.choose_weighted(rng, |(_, weight)| *weight)
+			else {
+				break;
+			};
+			item_candidates.retain(|(candidate, _)| *candidate != coords);
+			level.items.insert(coords, share(Item::new(kind, coords)));
 		}
 
-		level
+		Ok(level)
+	}
+
+	/// A Dijkstra map to the tiles identified by `is_goal`, through open
+	/// terrain, ignoring creatures.
+	fn terrain_map(&self, is_goal: impl Fn(&TilePoint) -> bool) -> DijkstraMap {
+		DijkstraMap::new(self.terrain.keys().copied(), is_goal, |coords| {
+			!matches!(self.terrain.get(coords), Some(Tile::Floor(_)))
+		})
+	}
+
+	/// Spawns a patrolling enemy of the given `species` at `coords`, if there's
+	/// room.
+	fn spawn_enemy(&mut self, species: Species, coords: TilePoint) {
+		// Ignore failure to spawn.
+		let _ = self.spawn(share(Creature::new(
+			Faction::Enemy,
+			species,
+			Behavior::Patrolling { destination: None },
+			coords,
+		)));
 	}
 
 	fn update_enemies_dijkstra_map(&mut self, faction: Faction) {
@@ -452,6 +513,120 @@ impl Level {
 		&self.dijkstra_maps
 	}
 
+	/// The level's rooms and the corridors between them.
+	pub fn room_graph(&self) -> &RoomGraph {
+		&self.room_graph
+	}
+
+	/// Statistics describing the layout generated for this level.
+	pub fn generation_stats(&self) -> &GenerationStats {
+		&self.generation_stats
+	}
+
+	/// Connectivity and playability measurements of the level.
+	pub fn analysis(&self) -> &LevelAnalysis {
+		&self.analysis
+	}
+
+	/// The mapping from the tiles in view to the screen.
+	pub fn tile_layout(&self) -> &TileLayout {
+		self.camera.tile_layout()
+	}
+
+	/// Scrolls the view of the level to follow `focus`.
+	pub fn update_camera(&mut self, focus: TilePoint) {
+		self.camera.follow(focus);
+	}
+
+	/// Shows the level in a new region of the screen.
+	pub fn set_viewport(&mut self, viewport: ScreenRectangle) {
+		self.camera.set_viewport(viewport);
+	}
+
+	/// How far below the surface the level is.
+	pub fn depth(&self) -> u32 {
+		self.depth
+	}
+
+	/// The number of turns that have passed in the level.
+	pub fn turn(&self) -> u32 {
+		self.turn
+	}
+
+	/// The level's creatures, including the player if spawned.
+	pub fn creatures(&self) -> impl Iterator<Item = &Shared<Creature>> {
+		self.creatures.values()
+	}
+
+	/// The level's items.
+	pub fn items(&self) -> impl Iterator<Item = &Shared<Item>> {
+		self.items.values()
+	}
+
+	/// The whole level as text, one line per row of tiles, regardless of what
+	/// the player can see. Each tile shows its creature, else its item, else
+	/// its terrain.
+	pub fn ascii_map(&self) -> String {
+		let mut renderer = TextRenderer::new(self.camera.bounds());
+		self.draw_objects(&mut renderer, |_| true, |_| Pose::STILL);
+		renderer.frame()
+	}
+
+	/// The creatures the player can currently see.
+	pub fn visible_creatures(&self) -> impl Iterator<Item = &Shared<Creature>> {
+		self.creatures
+			.iter()
+			.filter(|(coords, _)| self.vision.contains(coords))
+			.map(|(_, creature)| creature)
+	}
+
+	/// The terrain the player remembers at `coords`, if any.
+	pub fn remembered(&self, coords: TilePoint) -> Option<Tile> {
+		self.memory.get(&coords).copied()
+	}
+
+	/// Describes what the player knows about the tile at `coords`, or returns
+	/// `None` if the player has never seen it. Creatures and items are only
+	/// described while the tile is in view, since they may have moved.
+	pub fn describe(&self, coords: TilePoint) -> Option<String> {
+		let tile = self.memory.get(&coords)?;
+		if !self.vision.contains(&coords) {
+			return Some(format!("{} (remembered).", capitalized(tile.name())));
+		}
+		let mut description = format!("{}.", capitalized(tile.name()));
+		if let Some(creature) = self.creatures.get(&coords) {
+			let creature = creature.borrow();
+			if creature.faction == Faction::Ally
+				&& creature.species == Species::Human
+			{
+				description.push_str(" You are here.");
+			} else {
+				description.push_str(&format!(
+					" {} ({}, {}/{} health) is here.",
+					capitalized(creature.species.name()),
+					creature.faction.name(),
+					creature.stats.health(),
+					creature.max_health(),
+				));
+			}
+		}
+		if let Some(item) = self.items.get(&coords) {
+			let name = capitalized(item.borrow().kind.name());
+			description.push_str(&format!(" {name} lies here."));
+		}
+		Some(description)
+	}
+
+	/// A Dijkstra map to `destination` through the floor the player remembers,
+	/// for travelling without wandering into the unknown.
+	pub fn travel_map(&self, destination: TilePoint) -> DijkstraMap {
+		DijkstraMap::new(
+			self.memory.keys().copied(),
+			|coords| *coords == destination,
+			|coords| !matches!(self.memory.get(coords), Some(Tile::Floor(_))),
+		)
+	}
+
 	/// Updates vision and memory using the given viewer `origin`.
 	pub fn update_vision(&mut self, origin: TilePoint) {
 		self.vision = vision::get_vision(origin, |coords: &TilePoint| {
@@ -464,36 +639,78 @@ impl Level {
 		}
 	}
 
-	/// Draw everything in the level.
-	pub fn draw(&self, canvas: &mut Canvas, meshes: &Meshes) {
+	/// Whether the player can currently see `coords`, within the camera's
+	/// window.
+	pub fn in_view(&self, coords: TilePoint) -> bool {
+		self.camera.window().contains(coords) && self.vision.contains(&coords)
+	}
+
+	/// Draw everything in view of the camera, posing each creature with `pose`.
+	pub fn draw(
+		&self,
+		renderer: &mut dyn Renderer,
+		pose: impl Fn(&Shared<Creature>) -> Pose,
+	) {
+		let window = self.camera.window();
 		// Draw all remembered tiles that are not currently visible.
 		for (coords, tile) in &self.memory {
-			if !self.vision.contains(coords) {
-				tile.draw(
-					canvas,
-					meshes,
-					&self.tile_layout,
+			if window.contains(*coords) && !self.vision.contains(coords) {
+				renderer.draw_tile(
 					*coords,
+					*tile,
+					self.wall_mask(*coords),
 					Perception::Remembered,
 				);
 			}
 		}
 		// Draw visible tiles and objects.
+		self.draw_objects(renderer, |coords| self.in_view(*coords), pose);
+	}
+
+	/// Which of the cardinal neighbors of `coords` the player knows to be
+	/// walls.
+	fn wall_mask(&self, coords: TilePoint) -> WallMask {
+		[TILE_UP, TILE_RIGHT, TILE_DOWN, TILE_LEFT]
+			.into_iter()
+			.enumerate()
+			.filter(|(_, offset)| {
+				self.memory.get(&(coords + *offset)) == Some(&Tile::Wall)
+			})
+			.fold(0, |mask, (i, _)| mask | 1 << i)
+	}
+
+	/// Draws the terrain, items, and creatures at coordinates where
+	/// `is_visible` holds, as seen, posing each creature with `pose`.
+	fn draw_objects(
+		&self,
+		renderer: &mut dyn Renderer,
+		is_visible: impl Fn(&TilePoint) -> bool,
+		pose: impl Fn(&Shared<Creature>) -> Pose,
+	) {
 		for (coords, tile) in &self.terrain {
-			if self.vision.contains(coords) {
-				tile.draw(
-					canvas,
-					meshes,
-					&self.tile_layout,
+			if is_visible(coords) {
+				renderer.draw_tile(
 					*coords,
+					*tile,
+					self.wall_mask(*coords),
 					Perception::Seen,
 				);
 			}
 		}
-		for creature in self.creatures.values() {
-			let creature = creature.borrow();
-			if self.vision.contains(&creature.coords) {
-				creature.draw(canvas, meshes, &self.tile_layout);
+		for item in self.items.values() {
+			let item = item.borrow();
+			if is_visible(&item.coords) {
+				renderer.draw_item(item.coords, item.kind);
+			}
+		}
+		for shared in self.creatures.values() {
+			let creature = shared.borrow();
+			if is_visible(&creature.coords) {
+				renderer.draw_creature(
+					creature.coords,
+					creature.species,
+					pose(shared),
+				);
 			}
 		}
 	}
@@ -501,6 +718,7 @@ impl Level {
 	/// Advance time in the level by one turn, allowing NPCs to take their
 	/// turns.
 	pub fn update(&mut self, rng: &mut Pcg32) {
+		self.turn += 1;
 		let mut queue = self.creatures.values().cloned().collect::<Vec<_>>();
 		while let Some(creature) = queue.pop() {
 			let mut creature = creature.borrow_mut();
@@ -512,16 +730,17 @@ impl Level {
 		}
 	}
 
-	/// Spawns the player character at an arbitrary open tile. Panics if a spot
-	/// can't be found.
-	pub fn spawn_player(&mut self, rng: &mut Pcg32) -> Shared<Creature> {
+	/// Spawns the player character at the level's starting point, which is in
+	/// the entrance room if there is one. Generation leaves the start open, so
+	/// this only panics if called more than once.
+	pub fn spawn_player(&mut self) -> Shared<Creature> {
 		self.spawn(share(Creature::new(
 			Faction::Ally,
 			Species::Human,
 			// The player's creature is controlled separately, so just idle
 			// during level updates.
 			Behavior::Idle,
-			*self.unoccupied_coords().choose(rng).unwrap(),
+			self.start,
 		)))
 		.unwrap()
 	}
@@ -560,13 +779,17 @@ impl Level {
 		self.creatures.insert(to, removed);
 	}
 
-	/// All floor tile coordinates not occupied by a creature.
+	/// All floor tile coordinates not occupied by a creature, in row-major
+	/// order so that random choices among them depend only on the seed.
 	fn unoccupied_coords(&self) -> Vec<TilePoint> {
-		self.terrain
+		let mut coords = self
+			.terrain
 			.keys()
 			.copied()
 			.filter(|coords| self.collision(coords).is_none())
-			.collect()
+			.collect::<Vec<_>>();
+		coords.sort_by_key(|coords| (coords.y, coords.x));
+		coords
 	}
 
 	fn spawn(
@@ -606,3 +829,64 @@ impl Level {
 		}
 	}
 }
+
+/// `s` with its first letter in upper case.
+pub fn capitalized(s: &str) -> String {
+	let mut chars = s.chars();
+	chars
+		.next()
+		.map(|first| first.to_uppercase().chain(chars).collect())
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A square tileport in a wide viewport, leaving a bar on each side.
+	fn layout() -> TileLayout {
+		TileLayout::new(
+			ScreenRectangle {
+				pos: ScreenPoint::new(0.0, 100.0),
+				size: ScreenVector::new(1000.0, 500.0),
+			},
+			TileRectangle {
+				pos: TilePoint::new(3, 4),
+				size: TileVector::new(10, 10),
+			},
+		)
+	}
+
+	#[test]
+	fn to_tile_inverts_to_screen() {
+		let layout = layout();
+		for coords in layout.tileport().points() {
+			let screen_tile = layout.to_screen(coords);
+			let center = screen_tile.pos + screen_tile.size / 2.0;
+			assert_eq!(layout.to_tile(screen_tile.pos), Some(coords));
+			assert_eq!(layout.to_tile(center), Some(coords));
+		}
+	}
+
+	#[test]
+	fn to_tile_is_none_outside_the_tiles() {
+		let layout = layout();
+		for (x, y) in [
+			// Above, below, and beside the viewport.
+			(500.0, 50.0),
+			(500.0, 600.0),
+			(-10.0, 300.0),
+			(1010.0, 300.0),
+			// In the bars beside the square of tiles.
+			(100.0, 300.0),
+			(249.0, 300.0),
+			(750.0, 300.0),
+		] {
+			assert_eq!(layout.to_tile(ScreenPoint::new(x, y)), None);
+		}
+		assert_eq!(
+			layout.to_tile(ScreenPoint::new(250.0, 100.0)),
+			Some(TilePoint::new(3, 4))
+		);
+	}
+}
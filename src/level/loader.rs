@@ -0,0 +1,193 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+	analysis::LevelAnalysis,
+	camera::Camera,
+	creature::Species,
+	generation::{GenerationStats, RoomGraph},
+	geometry::{ScreenRectangle, TilePoint, TileRectangle, TileVector},
+	item::{Item, ItemKind},
+	level::{Floor, Level, Tile},
+	shared::share,
+};
+
+/// Reasons loading a level from text can fail. Lines and columns count from 1.
+#[derive(Debug)]
+pub enum LoadError {
+	/// A character that doesn't stand for any tile, creature, or item.
+	UnrecognizedCharacter {
+		line: usize,
+		column: usize,
+		character: char,
+	},
+	/// A second player start, after one was already found.
+	DuplicateStart { line: usize, column: usize },
+	/// The level has no player start.
+	MissingStart,
+}
+
+impl fmt::Display for LoadError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			LoadError::UnrecognizedCharacter {
+				line,
+				column,
+				character,
+			} => write!(
+				f,
+				"line {line}, column {column}: unrecognized character \
+				 {character:?}"
+			),
+			LoadError::DuplicateStart { line, column } => write!(
+				f,
+				"line {line}, column {column}: the level already has a player \
+				 start"
+			),
+			LoadError::MissingStart => {
+				write!(f, "the level has no player start")
+			}
+		}
+	}
+}
+
+impl std::error::Error for LoadError {}
+
+impl Level {
+	/// Loads a level from `text`, drawn using the same characters as
+	/// [`Level::ascii_map`], to be displayed in `viewport` with tiles of the
+	/// given on-screen `tile_size`. Creatures and items stand on stone floor,
+	/// and the player's start is marked with a human. Spaces are left empty.
+	/// The level is at depth 1. Fails if a character isn't recognized or if
+	/// there isn't exactly one player start.
+	pub fn load(
+		text: &str,
+		viewport: ScreenRectangle,
+		tile_size: f32,
+	) -> Result<Level, LoadError> {
+		let mut terrain = HashMap::new();
+		let mut enemies = Vec::new();
+		let mut items = Vec::new();
+		let mut start = None;
+		let mut size = TileVector::new(0, 0);
+		for (y, line) in text.lines().enumerate() {
+			for (x, character) in line.chars().enumerate() {
+				if character == ' ' {
+					continue;
+				}
+				let coords = TilePoint::new(x as i32, y as i32);
+				if let Some(tile) = Tile::ALL
+					.into_iter()
+					.find(|tile| tile.appearance().glyph == character)
+				{
+					terrain.insert(coords, tile);
+					continue;
+				}
+				terrain.insert(coords, Tile::Floor(Floor::Stone));
+				if let Some(species) = Species::ALL
+					.into_iter()
+					.find(|species| species.appearance().glyph == character)
+				{
+					if species != Species::Human {
+						enemies.push((species, coords));
+					} else if start.replace(coords).is_some() {
+						return Err(LoadError::DuplicateStart {
+							line: y + 1,
+							column: x + 1,
+						});
+					}
+				} else if let Some(kind) = ItemKind::ALL
+					.into_iter()
+					.find(|kind| kind.appearance().glyph == character)
+				{
+					items.push((kind, coords));
+				} else {
+					return Err(LoadError::UnrecognizedCharacter {
+						line: y + 1,
+						column: x + 1,
+						character,
+					});
+				}
+			}
+			size.x = size.x.max(line.chars().count() as i32);
+			size.y = y as i32 + 1;
+		}
+		let start = start.ok_or(LoadError::MissingStart)?;
+
+		let tileport = TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size,
+		};
+		let analysis = LevelAnalysis::new(&terrain, tileport, start);
+		let mut level = Level::new(
+			Camera::new(viewport, tileport, tile_size),
+			terrain,
+			RoomGraph::default(),
+			start,
+			GenerationStats::default(),
+			analysis,
+			1,
+		);
+		for (species, coords) in enemies {
+			level.spawn_enemy(species, coords);
+		}
+		for (kind, coords) in items {
+			level.items.insert(coords, share(Item::new(kind, coords)));
+		}
+		Ok(level)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::geometry::{ScreenPoint, ScreenVector};
+
+	const VIEWPORT: ScreenRectangle = ScreenRectangle {
+		pos: ScreenPoint::new(0.0, 0.0),
+		size: ScreenVector::new(640.0, 480.0),
+	};
+
+	fn load(text: &str) -> Result<Level, LoadError> {
+		Level::load(text, VIEWPORT, 16.0)
+	}
+
+	#[test]
+	fn loads_terrain_creatures_and_items() {
+		let text = "#####\n#@.g#\n#\"=$#\n#####\n";
+		let mut level = load(text).unwrap();
+		assert_eq!(level.creatures().count(), 1);
+		assert_eq!(level.items().count(), 1);
+		level.spawn_player();
+		assert_eq!(level.ascii_map(), text);
+	}
+
+	#[test]
+	fn reports_unrecognized_characters() {
+		match load("###\n#@x\n###") {
+			Err(LoadError::UnrecognizedCharacter {
+				line,
+				column,
+				character,
+			}) => assert_eq!((line, column, character), (2, 3, 'x')),
+			_ => panic!("expected an unrecognized character"),
+		}
+	}
+
+	#[test]
+	fn requires_a_player_start() {
+		assert!(matches!(
+			load("###\n#.#\n###"),
+			Err(LoadError::MissingStart)
+		));
+	}
+
+	#[test]
+	fn rejects_a_second_player_start() {
+		match load("#####\n#@.@#\n#####") {
+			Err(LoadError::DuplicateStart { line, column }) => {
+				assert_eq!((line, column), (2, 4))
+			}
+			_ => panic!("expected a duplicate start"),
+		}
+	}
+}
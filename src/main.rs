@@ -1,51 +1,219 @@
+mod analysis;
+mod animation;
+mod appearance;
+mod camera;
 mod creature;
 mod dijkstra_map;
 mod disjoint_sets;
 mod game_state;
+mod generation;
 mod geometry;
+mod hud;
 mod item;
+mod item_table;
 mod level;
 mod meshes;
+mod preview;
+mod renderer;
 mod shared;
+mod spawn_table;
+mod tileset;
+mod tuning;
 mod vision;
 
+use creature::Species;
 use game_state::GameState;
+use generation::{
+	BspGenerator, CaveGenerator, LevelGenerator, RoomGenerator, Vault,
+	WfcGenerator,
+};
 use geometry::{
 	ScreenPoint, ScreenRectangle, ScreenVector, TilePoint, TileRectangle,
 	TileVector,
 };
 use ggez::{
 	conf::{WindowMode, WindowSetup},
-	event, GameResult,
+	event, GameError, GameResult,
 };
-use level::Level;
+use item::ItemKind;
+use item_table::{ItemEntry, ItemTable};
+use level::{GenerationConfig, Level};
 use meshes::Meshes;
 use rand::prelude::*;
 use rand_pcg::Pcg32;
+use renderer::GlyphRenderer;
+use spawn_table::{SpawnEntry, SpawnTable};
+use tileset::Tileset;
+use tuning::Tuning;
 
-fn main() -> GameResult {
-	let viewport = ScreenRectangle {
-		pos: ScreenPoint::new(0.0, 0.0),
-		size: ScreenVector::new(1920.0, 1080.0),
+/// The region of the screen the game is laid out for, including the HUD.
+const VIEWPORT: ScreenRectangle = ScreenRectangle {
+	pos: ScreenPoint::new(0.0, 0.0),
+	size: ScreenVector::new(1920.0, 1080.0),
+};
+
+/// The preferred on-screen width and height of a tile.
+const TILE_SIZE: f32 = 30.0;
+
+/// The names of the level generators the game chooses between.
+const GENERATOR_NAMES: [&str; 4] = ["rooms", "bsp", "caves", "wfc"];
+
+/// The level generator called `name`, configured by `tuning`, or `None` if
+/// `name` isn't one of [`GENERATOR_NAMES`].
+fn generator(name: &str, tuning: &Tuning) -> Option<Box<dyn LevelGenerator>> {
+	let generator: Box<dyn LevelGenerator> = match name {
+		"rooms" => Box::new(RoomGenerator {
+			min_floor_ratio: tuning.room_floor_ratio,
+			min_room_size: tuning.min_room_size,
+			max_room_size: tuning.max_room_size,
+			shaped_room_ratio: tuning.shaped_room_ratio,
+			loop_ratio: tuning.loop_ratio,
+			vaults: [
+				include_str!("../resources/vaults/armory.txt"),
+				include_str!("../resources/vaults/garden.txt"),
+				include_str!("../resources/vaults/pillared_hall.txt"),
+				include_str!("../resources/vaults/shrine.txt"),
+			]
+			.into_iter()
+			.map(Vault::new)
+			.collect(),
+			vault_chance: tuning.vault_chance,
+		}),
+		"bsp" => Box::new(BspGenerator {
+			min_room_size: tuning.min_room_size,
+			max_room_size: tuning.max_room_size,
+			shaped_room_ratio: tuning.shaped_room_ratio,
+		}),
+		"caves" => Box::new(CaveGenerator {
+			fill_ratio: tuning.fill_ratio,
+			smoothing_iterations: tuning.smoothing_iterations,
+			min_region_size: tuning.cave_region_size,
+			join_regions: tuning.join_regions,
+		}),
+		"wfc" => Box::new(WfcGenerator::new(
+			include_str!("../resources/samples/dungeon.txt"),
+			tuning.pattern_size,
+			tuning.wfc_region_size,
+			tuning.wfc_attempts,
+		)),
+		_ => return None,
 	};
-	let mut rng: Pcg32 = Pcg32::from_entropy();
-	let mut level = Level::generate(
-		level::GenerationConfig {
-			viewport,
-			// 30-px tiles fitting snugly in a 1920 x 1080 viewport
-			tileport: TileRectangle {
-				pos: TilePoint::new(0, 0),
-				size: TileVector::new(64, 36),
-			},
-			min_floor_ratio: 0.4,
-			min_room_size: 3,
-			max_room_size: 15,
+	Some(generator)
+}
+
+/// The game's level generation settings, adjusted by `tuning`, using
+/// `generator` to lay out terrain.
+fn generation_config(
+	generator: Box<dyn LevelGenerator>,
+	tuning: &Tuning,
+) -> GenerationConfig {
+	GenerationConfig {
+		viewport: hud::split(VIEWPORT).0,
+		tileport: TileRectangle {
+			pos: TilePoint::new(0, 0),
+			size: TileVector::new(64, 36),
 		},
-		&mut rng,
-	);
-	let player = level.spawn_player(&mut rng);
+		// A 64 x 36 level fits the height of a 1920 x 1080 screen, scrolling
+		// sideways to make room for the HUD.
+		tile_size: TILE_SIZE,
+		generator,
+		depth: 1,
+		spawn_table: SpawnTable {
+			entries: vec![
+				SpawnEntry {
+					species: Species::Goblin,
+					weight: 17,
+					group_size: 1..=3,
+					depths: 1..=10,
+				},
+				SpawnEntry {
+					species: Species::Ogre,
+					weight: 3,
+					group_size: 1..=1,
+					depths: 1..=u32::MAX,
+				},
+			],
+			density: tuning.spawn_density,
+			safe_radius: tuning.safe_radius,
+		},
+		item_table: ItemTable {
+			entries: vec![
+				ItemEntry {
+					kind: ItemKind::Gold,
+					weight: 10,
+					depths: 1..=u32::MAX,
+				},
+				ItemEntry {
+					kind: ItemKind::Potion,
+					weight: 5,
+					depths: 1..=u32::MAX,
+				},
+				ItemEntry {
+					kind: ItemKind::Scroll,
+					weight: 3,
+					depths: 2..=u32::MAX,
+				},
+				ItemEntry {
+					kind: ItemKind::Weapon,
+					weight: 2,
+					depths: 1..=u32::MAX,
+				},
+			],
+			treasure_bias: tuning.treasure_bias,
+		},
+		item_budget: tuning.item_budget,
+		requirements: tuning.requirements.clone(),
+		max_attempts: tuning.max_attempts,
+	}
+}
+
+fn main() -> GameResult {
+	let mut args = std::env::args().skip(1).peekable();
+	if args.peek().map(String::as_str) == Some("preview") {
+		args.next();
+		if let Err(error) = preview::run(args) {
+			eprintln!("{error}");
+			std::process::exit(1);
+		}
+		return Ok(());
+	}
+	let mut level_path = None;
+	let mut tileset_path = None;
+	while let Some(arg) = args.next() {
+		if arg == "--tileset" {
+			tileset_path = Some(args.next().ok_or_else(|| {
+				GameError::CustomError("--tileset needs a path".to_string())
+			})?);
+		} else {
+			level_path = Some(arg);
+		}
+	}
+
+	let mut rng: Pcg32 = Pcg32::from_entropy();
+	let mut level = match level_path {
+		// Play a hand-authored level if given one.
+		Some(path) => {
+			let text = std::fs::read_to_string(&path)?;
+			Level::load(&text, hud::split(VIEWPORT).0, TILE_SIZE).map_err(
+				|error| GameError::CustomError(format!("{path}: {error}")),
+			)?
+		}
+		None => {
+			// Mix classic room floors, cave floors, and floors resembling a
+			// sample.
+			let name = GENERATOR_NAMES.choose(&mut rng).unwrap();
+			let tuning = Tuning::default();
+			Level::generate(
+				generation_config(generator(name, &tuning).unwrap(), &tuning),
+				&mut rng,
+			)
+			.map_err(|error| GameError::CustomError(error.to_string()))?
+		}
+	};
+	let player = level.spawn_player();
 	level.update_dijkstra_maps();
 	level.update_vision(player.borrow().coords);
+	level.update_camera(player.borrow().coords);
 
 	let (mut ctx, event_loop) =
 		ggez::ContextBuilder::new("RL", "Jonathan Sharman")
@@ -55,8 +223,8 @@ fn main() -> GameResult {
 				..Default::default()
 			})
 			.window_mode(WindowMode {
-				width: viewport.size.x,
-				height: viewport.size.y,
+				width: VIEWPORT.size.x,
+				height: VIEWPORT.size.y,
 				maximized: true,
 				fullscreen_type: ggez::conf::FullscreenType::Desktop,
 				resizable: true,
@@ -64,11 +232,12 @@ fn main() -> GameResult {
 			})
 			.build()?;
 	let meshes = Meshes::new(&mut ctx)?;
-	let state = GameState {
-		rng,
-		player,
-		level,
-		meshes,
-	};
+	GlyphRenderer::add_font(&mut ctx)?;
+	let tileset = tileset_path
+		.map(|path| Tileset::load(&ctx, path.as_ref()))
+		.transpose()?;
+	let mut state = GameState::new(rng, player, level, meshes, tileset);
+	// The window may not have the requested size, e.g. once maximized.
+	state.fit_to_window(&ctx);
 	event::run(ctx, event_loop, state);
 }
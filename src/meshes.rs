@@ -1,9 +1,17 @@
+use std::collections::HashMap;
+
 use ggez::{
 	glam::Vec2,
-	graphics::{Color, DrawMode, Mesh, Rect},
+	graphics::{Color, DrawMode, InstanceArray, Mesh, Rect},
 	Context, GameResult,
 };
 
+use crate::{
+	creature::Species,
+	item::ItemKind,
+	level::{Floor, Tile},
+};
+
 pub struct Meshes {
 	pub wall: Mesh,
 	pub stone_floor: Mesh,
@@ -14,7 +22,17 @@ pub struct Meshes {
 	pub goblin: Mesh,
 	pub ogre: Mesh,
 	// Items
-	pub item: Mesh,
+	pub gold: Mesh,
+	pub potion: Mesh,
+	pub scroll: Mesh,
+	pub weapon: Mesh,
+	// Interface
+	/// An outline around a tile.
+	pub highlight: Mesh,
+	/// A white rectangle, to be tinted as a background.
+	pub panel: Mesh,
+	/// Buffers for drawing many tiles of each kind at once.
+	pub tile_instances: HashMap<Tile, InstanceArray>,
 }
 
 impl Meshes {
@@ -29,7 +47,7 @@ impl Meshes {
 					w: 1.0,
 					h: 1.0,
 				},
-				Color::from_rgb(128, 0, 0),
+				Tile::Wall.appearance().color,
 			)?,
 			stone_floor: Mesh::new_rectangle(
 				ctx,
@@ -40,7 +58,7 @@ impl Meshes {
 					w: 1.0,
 					h: 1.0,
 				},
-				Color::from_rgb(128, 128, 128),
+				Tile::Floor(Floor::Stone).appearance().color,
 			)?,
 			grass_floor: Mesh::new_rectangle(
 				ctx,
@@ -51,7 +69,7 @@ impl Meshes {
 					w: 1.0,
 					h: 1.0,
 				},
-				Color::from_rgb(35, 80, 25),
+				Tile::Floor(Floor::Grass).appearance().color,
 			)?,
 			wood_floor: Mesh::new_rectangle(
 				ctx,
@@ -62,7 +80,7 @@ impl Meshes {
 					w: 1.0,
 					h: 1.0,
 				},
-				Color::from_rgb(96, 58, 32),
+				Tile::Floor(Floor::Wood).appearance().color,
 			)?,
 			human: Mesh::new_ellipse(
 				ctx,
@@ -71,7 +89,7 @@ impl Meshes {
 				0.5,
 				0.5,
 				1.0,
-				Color::from_rgb(0, 192, 255),
+				Species::Human.appearance().color,
 			)?,
 			goblin: Mesh::new_ellipse(
 				ctx,
@@ -80,7 +98,7 @@ impl Meshes {
 				0.5,
 				0.5,
 				1.0,
-				Color::from_rgb(0, 160, 0),
+				Species::Goblin.appearance().color,
 			)?,
 			ogre: Mesh::new_ellipse(
 				ctx,
@@ -89,19 +107,84 @@ impl Meshes {
 				0.5,
 				0.5,
 				1.0,
-				Color::RED,
+				Species::Ogre.appearance().color,
+			)?,
+			gold: Mesh::new_ellipse(
+				ctx,
+				DrawMode::fill(),
+				Vec2::new(0.0, 0.0),
+				0.25,
+				0.25,
+				1.0,
+				ItemKind::Gold.appearance().color,
 			)?,
-			item: Mesh::new_rectangle(
+			potion: Mesh::new_ellipse(
+				ctx,
+				DrawMode::fill(),
+				Vec2::new(0.0, 0.0),
+				0.2,
+				0.3,
+				1.0,
+				ItemKind::Potion.appearance().color,
+			)?,
+			scroll: Mesh::new_rectangle(
 				ctx,
 				DrawMode::fill(),
 				Rect {
-					x: -0.4,
+					x: -0.3,
+					y: -0.2,
+					w: 0.6,
+					h: 0.4,
+				},
+				ItemKind::Scroll.appearance().color,
+			)?,
+			weapon: Mesh::new_rectangle(
+				ctx,
+				DrawMode::fill(),
+				Rect {
+					x: -0.05,
 					y: -0.4,
-					w: 0.8,
+					w: 0.1,
 					h: 0.8,
 				},
+				ItemKind::Weapon.appearance().color,
+			)?,
+			highlight: Mesh::new_rectangle(
+				ctx,
+				DrawMode::stroke(0.1),
+				Rect {
+					x: 0.0,
+					y: 0.0,
+					w: 1.0,
+					h: 1.0,
+				},
 				Color::YELLOW,
 			)?,
+			panel: Mesh::new_rectangle(
+				ctx,
+				DrawMode::fill(),
+				Rect {
+					x: 0.0,
+					y: 0.0,
+					w: 1.0,
+					h: 1.0,
+				},
+				Color::WHITE,
+			)?,
+			tile_instances: Tile::ALL
+				.into_iter()
+				.map(|tile| (tile, InstanceArray::new(ctx, None)))
+				.collect(),
 		})
 	}
+
+	/// The mesh for drawing `tile`.
+	pub fn tile(&self, tile: Tile) -> &Mesh {
+		match tile {
+			Tile::Floor(Floor::Stone) => &self.stone_floor,
+			Tile::Floor(Floor::Grass) => &self.grass_floor,
+			Tile::Floor(Floor::Wood) => &self.wood_floor,
+			Tile::Wall => &self.wall,
+		}
+	}
 }
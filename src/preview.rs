@@ -0,0 +1,362 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_pcg::Pcg32;
+
+use crate::{
+	creature::Species, generation_config, generator, geometry::TileVector,
+	item::ItemKind, level::Level, tuning::Tuning, GENERATOR_NAMES,
+};
+
+const USAGE: &str = "\
+Usage: rl preview [options]
+
+Generates levels using the game's generation settings, printing each as ASCII
+along with statistics about the whole batch.
+
+Options:
+  --generator NAME  Use the named generator (rooms, bsp, caves, or wfc)
+                    instead of choosing one at random for each seed.
+  --seeds SEEDS     The seed or range of seeds (e.g. 7 or 0..10) to generate
+                    levels for. Defaults to 0..5.
+  --depth DEPTH     The depth of the generated levels. Defaults to 1.
+  --size WxH        The size of the generated levels in tiles. Defaults to
+                    64x36.
+  --stats-only      Print only statistics, not maps.
+
+Generation settings, which default to the game's values:
+  --room-floor-ratio R      rooms: minimum proportion of floor
+  --min-room-size N         rooms, bsp: minimum room floor size
+  --max-room-size N         rooms, bsp: maximum room floor size
+  --shaped-room-ratio R     rooms, bsp: proportion of non-rectangular rooms
+  --loop-ratio R            rooms: extra connections per room
+  --vault-chance R          rooms: chance of placing each vault
+  --fill-ratio R            caves: initial proportion of rock
+  --smoothing-iterations N  caves: number of smoothing passes
+  --cave-region-size N      caves: minimum open region size
+  --join-regions BOOL       caves: whether to connect open regions
+  --pattern-size N          wfc: width and height of sample patterns
+  --wfc-region-size N       wfc: minimum open region size
+  --wfc-attempts N          wfc: restarts allowed after a contradiction
+  --spawn-density R         creatures per open floor tile
+  --safe-radius N           creature-free distance around the start
+  --item-budget N           items scattered per level
+  --treasure-bias N         item weight of dead ends and vaults
+  --connected BOOL          require every open tile to be reachable
+  --min-floor-ratio R       required proportion of floor
+  --max-dead-ends N         allowed number of dead ends
+  --min-longest-path N      required estimated longest path
+  --max-attempts N          generation attempts per level";
+
+/// Settings for a preview run, parsed from the command line.
+struct Options {
+	/// The generator to use, or `None` to choose one at random per seed.
+	generator: Option<String>,
+	seeds: RangeInclusive<u64>,
+	depth: u32,
+	size: Option<TileVector>,
+	show_maps: bool,
+	tuning: Tuning,
+}
+
+impl Options {
+	fn parse(
+		mut args: impl Iterator<Item = String>,
+	) -> Result<Options, String> {
+		let mut options = Options {
+			generator: None,
+			seeds: 0..=4,
+			depth: 1,
+			size: None,
+			show_maps: true,
+			tuning: Tuning::default(),
+		};
+		while let Some(arg) = args.next() {
+			let mut value = || {
+				args.next()
+					.ok_or_else(|| format!("{arg} needs a value\n\n{USAGE}"))
+			};
+			match arg.as_str() {
+				"--generator" => {
+					let name = value()?;
+					if !GENERATOR_NAMES.contains(&name.as_str()) {
+						return Err(format!("unknown generator {name:?}"));
+					}
+					options.generator = Some(name);
+				}
+				"--seeds" => options.seeds = parse_seeds(&value()?)?,
+				"--depth" => options.depth = parse_number(&value()?)?,
+				"--size" => {
+					let size = value()?;
+					let (width, height) = size
+						.split_once('x')
+						.ok_or_else(|| format!("invalid size {size:?}"))?;
+					options.size = Some(TileVector::new(
+						parse_number(width)?,
+						parse_number(height)?,
+					));
+				}
+				"--stats-only" => options.show_maps = false,
+				_ => match arg.strip_prefix("--") {
+					Some(name) => options
+						.tuning
+						.set(name, &value()?)
+						.map_err(|error| format!("{error}\n\n{USAGE}"))?,
+					None => {
+						return Err(format!(
+							"unknown option {arg:?}\n\n{USAGE}"
+						))
+					}
+				},
+			}
+		}
+		Ok(options)
+	}
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+	s.parse().map_err(|_| format!("invalid number {s:?}"))
+}
+
+/// Parses either a single seed or a non-empty, half-open range of seeds,
+/// `start..end`.
+fn parse_seeds(s: &str) -> Result<RangeInclusive<u64>, String> {
+	match s.split_once("..") {
+		Some((start, end)) => {
+			let (start, end) = (parse_number(start)?, parse_number(end)?);
+			if start >= end {
+				return Err(format!("seed range {s:?} is empty"));
+			}
+			Ok(start..=end - 1)
+		}
+		None => {
+			let seed = parse_number(s)?;
+			Ok(seed..=seed)
+		}
+	}
+}
+
+/// Measurements gathered across all the levels in a preview run.
+#[derive(Default)]
+struct Summary {
+	failures: usize,
+	floor_ratios: Vec<f32>,
+	rooms: Vec<usize>,
+	corridor_lengths: Vec<usize>,
+	creatures: BTreeMap<Species, usize>,
+	items: BTreeMap<ItemKind, usize>,
+}
+
+impl Summary {
+	/// Records the measurements of `level`.
+	fn add(&mut self, level: &Level) {
+		self.floor_ratios.push(level.analysis().floor_ratio);
+		self.rooms.push(level.room_graph().rooms.len());
+		self.corridor_lengths.push(corridor_length(level));
+		for creature in level.creatures() {
+			let species = creature.borrow().species;
+			if species != Species::Human {
+				*self.creatures.entry(species).or_default() += 1;
+			}
+		}
+		for item in level.items() {
+			*self.items.entry(item.borrow().kind).or_default() += 1;
+		}
+	}
+
+	fn print(&self) {
+		let levels = self.floor_ratios.len();
+		println!("Generated {levels} levels ({} failed).", self.failures);
+		if levels == 0 {
+			return;
+		}
+		print_range(
+			"Floor ratio",
+			self.floor_ratios.iter().map(|ratio| 100.0 * ratio),
+			"%",
+		);
+		print_range("Rooms", self.rooms.iter().map(|&n| n as f32), "");
+		print_range(
+			"Corridor length",
+			self.corridor_lengths.iter().map(|&n| n as f32),
+			"",
+		);
+		print_distribution("Creatures", &self.creatures, levels);
+		print_distribution("Items", &self.items, levels);
+	}
+}
+
+/// The total number of tiles in the level's corridors.
+fn corridor_length(level: &Level) -> usize {
+	level
+		.room_graph()
+		.corridors
+		.iter()
+		.map(|corridor| corridor.tiles.len())
+		.sum()
+}
+
+/// Prints the minimum, mean, and maximum of `values`, which must not be empty.
+fn print_range(label: &str, values: impl Iterator<Item = f32>, unit: &str) {
+	let values = values.collect::<Vec<_>>();
+	let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+	let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+	let mean = values.iter().sum::<f32>() / values.len() as f32;
+	println!(
+		"{label}: min {min:.0}{unit}, mean {mean:.1}{unit}, max {max:.0}{unit}"
+	);
+}
+
+/// Prints how many of each kind of thing were spawned, in total and per level.
+fn print_distribution<K: std::fmt::Debug>(
+	label: &str,
+	counts: &BTreeMap<K, usize>,
+	levels: usize,
+) {
+	let total = counts.values().sum::<usize>();
+	println!("{label}: {total} total");
+	for (kind, count) in counts {
+		println!(
+			"  {kind:?}: {count} ({:.0}%, {:.1} per level)",
+			100.0 * *count as f32 / total as f32,
+			*count as f32 / levels as f32,
+		);
+	}
+}
+
+/// Runs the level preview tool with the given command-line `args`, excluding
+/// the program name and subcommand. Fails if the arguments are invalid or if
+/// any level fails to generate.
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+	let args = args.collect::<Vec<_>>();
+	if args.iter().any(|arg| arg == "--help") {
+		println!("{USAGE}");
+		return Ok(());
+	}
+	let options = Options::parse(args.into_iter())?;
+	let mut summary = Summary::default();
+	for seed in options.seeds.clone() {
+		let mut rng = Pcg32::seed_from_u64(seed);
+		let name = match &options.generator {
+			Some(name) => name.as_str(),
+			None => GENERATOR_NAMES.choose(&mut rng).unwrap(),
+		};
+		let mut config = generation_config(
+			generator(name, &options.tuning).unwrap(),
+			&options.tuning,
+		);
+		config.depth = options.depth;
+		if let Some(size) = options.size {
+			config.tileport.size = size;
+		}
+		let mut level = match Level::generate(config, &mut rng) {
+			Ok(level) => level,
+			Err(error) => {
+				println!("Seed {seed} ({name}): failed: {error}\n");
+				summary.failures += 1;
+				continue;
+			}
+		};
+		// Show where the player would start.
+		level.spawn_player();
+		let analysis = level.analysis();
+		let stats = level.generation_stats();
+		println!(
+			"Seed {seed} ({name}): {:.0}% floor, {} rooms, {} loops, {} \
+			 corridor tiles, {} failed routes, {} dead ends, estimated \
+			 longest path {}",
+			100.0 * analysis.floor_ratio,
+			stats.rooms,
+			stats.cycles,
+			corridor_length(&level),
+			stats.failed_routes,
+			analysis.dead_ends,
+			analysis.longest_path_estimate,
+		);
+		if options.show_maps {
+			println!("{}", level.ascii_map());
+		}
+		summary.add(&level);
+	}
+	println!();
+	summary.print();
+	match summary.failures {
+		0 => Ok(()),
+		failures => Err(format!(
+			"{failures} of {} levels failed to generate",
+			failures + summary.floor_ratios.len(),
+		)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(args: &[&str]) -> Result<Options, String> {
+		Options::parse(args.iter().map(|arg| arg.to_string()))
+	}
+
+	#[test]
+	fn seeds_reproduce_levels() {
+		for name in GENERATOR_NAMES {
+			let tuning = Tuning::default();
+			let map = || {
+				let config = generation_config(
+					generator(name, &tuning).unwrap(),
+					&tuning,
+				);
+				let mut level =
+					Level::generate(config, &mut Pcg32::seed_from_u64(7))
+						.unwrap();
+				level.spawn_player();
+				level.ascii_map()
+			};
+			assert_eq!(map(), map(), "{name}");
+		}
+	}
+
+	#[test]
+	fn parses_seeds() {
+		assert_eq!(parse_seeds("5"), Ok(5..=5));
+		assert_eq!(parse_seeds("0..3"), Ok(0..=2));
+		assert_eq!(
+			parse_seeds("0..18446744073709551615"),
+			Ok(0..=u64::MAX - 1)
+		);
+		assert!(parse_seeds("3..3").is_err());
+		assert!(parse_seeds("3..1").is_err());
+		assert!(parse_seeds("x").is_err());
+	}
+
+	#[test]
+	fn parses_options() {
+		let options = parse(&[
+			"--generator",
+			"caves",
+			"--size",
+			"20x10",
+			"--loop-ratio",
+			"0.5",
+		])
+		.unwrap();
+		assert_eq!(options.generator.as_deref(), Some("caves"));
+		assert_eq!(options.size, Some(TileVector::new(20, 10)));
+		assert_eq!(options.tuning.loop_ratio, 0.5);
+	}
+
+	#[test]
+	fn rejects_bad_options() {
+		for args in [
+			&["--generator", "mazes"][..],
+			&["--seeds"],
+			&["--size", "20"],
+			&["--depth", "-1"],
+			&["--loop-ratio", "lots"],
+			&["--no-such-setting", "1"],
+			&["stats-only"],
+		] {
+			assert!(parse(args).is_err(), "{args:?}");
+		}
+	}
+}
@@ -0,0 +1,84 @@
+mod glyph;
+mod mesh;
+mod sprite;
+mod text;
+
+use ggez::graphics::{Color, ZIndex};
+
+use crate::{
+	creature::Species,
+	geometry::{ScreenVector, TilePoint},
+	item::ItemKind,
+	level::Tile,
+};
+
+pub use glyph::GlyphRenderer;
+pub use mesh::MeshRenderer;
+pub use sprite::SpriteRenderer;
+pub use text::TextRenderer;
+
+/// The z-index of draws that backends batch together, beneath anything drawn
+/// immediately.
+pub const BATCH_Z: ZIndex = -1;
+
+/// Bit flags for which of a tile's cardinal neighbors are walls, so that
+/// backends can join walls together: 1 for up, 2 for right, 4 for down, and 8
+/// for left.
+pub type WallMask = u8;
+
+/// How the player perceives a tile.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Perception {
+	/// The tile is currently visible.
+	Seen,
+	/// The tile isn't visible, but the player remembers it.
+	Remembered,
+}
+
+/// How to show a creature part way through an animation.
+#[derive(Clone, Copy, Debug)]
+pub struct Pose {
+	/// How far the creature appears from its tile, in tiles.
+	pub offset: ScreenVector,
+	/// A tint to multiply the creature's color by.
+	pub color: Color,
+}
+
+impl Pose {
+	/// A creature at rest on its tile.
+	pub const STILL: Pose = Pose {
+		offset: ScreenVector::new(0.0, 0.0),
+		color: Color::WHITE,
+	};
+}
+
+/// A backend for presenting what the player sees. Things drawn later appear
+/// on top of things drawn earlier at the same tile.
+pub trait Renderer {
+	fn draw_tile(
+		&mut self,
+		coords: TilePoint,
+		tile: Tile,
+		wall_mask: WallMask,
+		perception: Perception,
+	);
+
+	fn draw_item(&mut self, coords: TilePoint, kind: ItemKind);
+
+	/// Draws a creature at `coords` in the given `pose`. Backends that can't
+	/// show a creature between tiles may ignore the pose's offset.
+	fn draw_creature(
+		&mut self,
+		coords: TilePoint,
+		species: Species,
+		pose: Pose,
+	);
+
+	/// Marks the tile at `coords`, e.g. because it's under the mouse cursor.
+	fn draw_highlight(&mut self, coords: TilePoint);
+
+	/// Submits any draws the backend has batched. Call this once everything
+	/// has been drawn, before drawing anything else that depends on the
+	/// renderer's output.
+	fn finish(self: Box<Self>) {}
+}
@@ -0,0 +1,151 @@
+use ggez::{
+	graphics::{Canvas, DrawParam, FontData, Text, TextFragment},
+	Context, GameResult,
+};
+
+use crate::{
+	appearance::Appearance,
+	creature::Species,
+	geometry::{ScreenVector, TilePoint, TileVector},
+	item::ItemKind,
+	level::{Tile, TileLayout},
+	meshes::Meshes,
+	renderer::{Perception, Pose, Renderer, WallMask, BATCH_Z},
+};
+
+/// The name of the monospace font glyphs are drawn in.
+pub const GLYPH_FONT: &str = "DejaVu Sans Mono";
+
+/// Draws to a ggez canvas using colored characters, like a classic roguelike.
+/// Each tile shows only the last thing drawn on it. Glyphs are gathered into
+/// one line of text per row of tiles, stretched to fit the tiles and submitted
+/// beneath everything else when the renderer is finished.
+pub struct GlyphRenderer<'a> {
+	canvas: &'a mut Canvas,
+	/// Used for highlights, which aren't glyphs.
+	meshes: &'a Meshes,
+	tile_layout: &'a TileLayout,
+	/// The width of a glyph as tall as a tile.
+	advance: f32,
+	/// The glyph and color shown at each tile of the layout, in row-major
+	/// order.
+	cells: Vec<Option<Appearance>>,
+}
+
+impl<'a> GlyphRenderer<'a> {
+	/// Registers the bundled glyph font with `ctx`. This must be done before
+	/// creating a glyph renderer.
+	pub fn add_font(ctx: &mut Context) -> GameResult {
+		ctx.gfx.add_font(
+			GLYPH_FONT,
+			FontData::from_slice(include_bytes!(
+				"../../resources/fonts/DejaVuSansMono.ttf"
+			))?,
+		);
+		Ok(())
+	}
+
+	pub fn new(
+		ctx: &Context,
+		canvas: &'a mut Canvas,
+		meshes: &'a Meshes,
+		tile_layout: &'a TileLayout,
+	) -> GameResult<GlyphRenderer<'a>> {
+		let advance = Text::new("#")
+			.set_font(GLYPH_FONT)
+			.set_scale(tile_layout.tile_size().y)
+			.measure(ctx)?
+			.x;
+		Ok(GlyphRenderer {
+			canvas,
+			meshes,
+			tile_layout,
+			advance,
+			cells: vec![None; tile_layout.tileport().area() as usize],
+		})
+	}
+
+	/// Shows `appearance` at `coords`, ignoring coordinates outside the layout.
+	fn put(&mut self, coords: TilePoint, appearance: Appearance) {
+		let tileport = self.tile_layout.tileport();
+		if tileport.contains(coords) {
+			let offset = coords - tileport.pos;
+			let i = offset.y * tileport.size.x + offset.x;
+			self.cells[i as usize] = Some(appearance);
+		}
+	}
+}
+
+impl Renderer for GlyphRenderer<'_> {
+	fn draw_tile(
+		&mut self,
+		coords: TilePoint,
+		tile: Tile,
+		_: WallMask,
+		perception: Perception,
+	) {
+		let mut appearance = tile.appearance();
+		if perception == Perception::Remembered {
+			appearance.color.a = 0.25;
+		}
+		self.put(coords, appearance);
+	}
+
+	fn draw_item(&mut self, coords: TilePoint, kind: ItemKind) {
+		self.put(coords, kind.appearance());
+	}
+
+	fn draw_creature(
+		&mut self,
+		coords: TilePoint,
+		species: Species,
+		pose: Pose,
+	) {
+		// Glyphs stay in their cells, so only the tint applies.
+		let mut appearance = species.appearance();
+		let (color, tint) = (&mut appearance.color, pose.color);
+		color.r *= tint.r;
+		color.g *= tint.g;
+		color.b *= tint.b;
+		color.a *= tint.a;
+		self.put(coords, appearance);
+	}
+
+	fn draw_highlight(&mut self, coords: TilePoint) {
+		let screen_tile = self.tile_layout.to_screen(coords);
+		self.canvas.draw(
+			&self.meshes.highlight,
+			DrawParam::new()
+				.dest(screen_tile.pos)
+				.scale(screen_tile.size),
+		);
+	}
+
+	fn finish(self: Box<Self>) {
+		let tileport = self.tile_layout.tileport();
+		let tile_size = self.tile_layout.tile_size();
+		let rows = self.cells.chunks(tileport.size.x as usize);
+		for (y, row) in rows.enumerate() {
+			if row.iter().all(Option::is_none) {
+				continue;
+			}
+			let mut text = Text::default();
+			for cell in row {
+				text.add(match cell {
+					Some(appearance) => TextFragment::new(appearance.glyph)
+						.color(appearance.color),
+					None => TextFragment::new(' '),
+				});
+			}
+			text.set_font(GLYPH_FONT).set_scale(tile_size.y);
+			let start = tileport.pos + TileVector::new(0, y as i32);
+			self.canvas.draw(
+				&text,
+				DrawParam::new()
+					.dest(self.tile_layout.to_screen(start).pos)
+					.scale(ScreenVector::new(tile_size.x / self.advance, 1.0))
+					.z(BATCH_Z),
+			);
+		}
+	}
+}
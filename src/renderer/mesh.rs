@@ -0,0 +1,122 @@
+use ggez::graphics::{Canvas, Color, DrawParam};
+
+use crate::{
+	creature::Species,
+	geometry::{ScreenVector, TilePoint},
+	item::ItemKind,
+	level::{Tile, TileLayout},
+	meshes::Meshes,
+	renderer::{Perception, Pose, Renderer, WallMask, BATCH_Z},
+};
+
+/// Draws to a ggez canvas using flat colored meshes. Tiles are batched into
+/// one draw per kind of tile, submitted beneath everything else when the
+/// renderer is finished.
+pub struct MeshRenderer<'a> {
+	canvas: &'a mut Canvas,
+	meshes: &'a mut Meshes,
+	tile_layout: &'a TileLayout,
+}
+
+impl<'a> MeshRenderer<'a> {
+	pub fn new(
+		canvas: &'a mut Canvas,
+		meshes: &'a mut Meshes,
+		tile_layout: &'a TileLayout,
+	) -> MeshRenderer<'a> {
+		for instances in meshes.tile_instances.values_mut() {
+			instances.clear();
+		}
+		MeshRenderer {
+			canvas,
+			meshes,
+			tile_layout,
+		}
+	}
+}
+
+impl Renderer for MeshRenderer<'_> {
+	fn draw_tile(
+		&mut self,
+		coords: TilePoint,
+		tile: Tile,
+		_: WallMask,
+		perception: Perception,
+	) {
+		let color = match perception {
+			Perception::Seen => Color::WHITE,
+			Perception::Remembered => Color::from_rgba(255, 255, 255, 64),
+		};
+		let screen_tile = self.tile_layout.to_screen(coords);
+		self.meshes.tile_instances.get_mut(&tile).unwrap().push(
+			DrawParam::new()
+				.dest(screen_tile.pos)
+				.scale(screen_tile.size)
+				.color(color),
+		);
+	}
+
+	fn draw_item(&mut self, coords: TilePoint, kind: ItemKind) {
+		let screen_tile = self.tile_layout.to_screen(coords);
+		let mesh = match kind {
+			ItemKind::Gold => &self.meshes.gold,
+			ItemKind::Potion => &self.meshes.potion,
+			ItemKind::Scroll => &self.meshes.scroll,
+			ItemKind::Weapon => &self.meshes.weapon,
+		};
+		self.canvas.draw(
+			mesh,
+			DrawParam::new()
+				.dest(screen_tile.pos + screen_tile.size / 2.0)
+				.scale(screen_tile.size),
+		);
+	}
+
+	fn draw_creature(
+		&mut self,
+		coords: TilePoint,
+		species: Species,
+		pose: Pose,
+	) {
+		let screen_tile = self.tile_layout.to_screen(coords);
+		let offset = ScreenVector::new(
+			pose.offset.x * screen_tile.size.x,
+			pose.offset.y * screen_tile.size.y,
+		);
+		let mesh = match species {
+			Species::Human => &self.meshes.human,
+			Species::Goblin => &self.meshes.goblin,
+			Species::Ogre => &self.meshes.ogre,
+		};
+		self.canvas.draw(
+			mesh,
+			DrawParam::new()
+				.dest(screen_tile.pos + screen_tile.size / 2.0 + offset)
+				.scale(screen_tile.size)
+				.color(pose.color),
+		);
+	}
+
+	fn draw_highlight(&mut self, coords: TilePoint) {
+		let screen_tile = self.tile_layout.to_screen(coords);
+		self.canvas.draw(
+			&self.meshes.highlight,
+			DrawParam::new()
+				.dest(screen_tile.pos)
+				.scale(screen_tile.size),
+		);
+	}
+
+	fn finish(self: Box<Self>) {
+		for tile in Tile::ALL {
+			let instances = &self.meshes.tile_instances[&tile];
+			if !instances.instances().is_empty() {
+				self.canvas.draw_instanced_mesh(
+					self.meshes.tile(tile).clone(),
+					instances,
+					DrawParam::new().z(BATCH_Z),
+				);
+			}
+		}
+	}
+}
@@ -0,0 +1,119 @@
+use ggez::graphics::{Canvas, Color, DrawParam, Rect, Sampler};
+
+use crate::{
+	creature::Species,
+	geometry::{ScreenVector, TilePoint},
+	item::ItemKind,
+	level::{Tile, TileLayout},
+	meshes::Meshes,
+	renderer::{Perception, Pose, Renderer, WallMask, BATCH_Z},
+	tileset::Tileset,
+};
+
+/// Draws to a ggez canvas using sprites from a tileset. Sprites are batched
+/// into a single draw, submitted beneath everything else when the renderer is
+/// finished.
+pub struct SpriteRenderer<'a> {
+	canvas: &'a mut Canvas,
+	tileset: &'a mut Tileset,
+	/// Used for highlights, which aren't part of the tileset.
+	meshes: &'a Meshes,
+	tile_layout: &'a TileLayout,
+}
+
+impl<'a> SpriteRenderer<'a> {
+	pub fn new(
+		canvas: &'a mut Canvas,
+		tileset: &'a mut Tileset,
+		meshes: &'a Meshes,
+		tile_layout: &'a TileLayout,
+	) -> SpriteRenderer<'a> {
+		// Keep pixel art crisp when scaled up.
+		canvas.set_sampler(Sampler::nearest_clamp());
+		tileset.instances.clear();
+		SpriteRenderer {
+			canvas,
+			tileset,
+			meshes,
+			tile_layout,
+		}
+	}
+
+	/// Draws the sprite at `source` over the tile at `coords`, moved by
+	/// `offset` tiles.
+	fn draw_sprite(
+		&mut self,
+		coords: TilePoint,
+		offset: ScreenVector,
+		source: Rect,
+		color: Color,
+	) {
+		let screen_tile = self.tile_layout.to_screen(coords);
+		let cell_size = self.tileset.cell_size;
+		self.tileset.instances.push(
+			DrawParam::new()
+				.src(source)
+				.dest(
+					screen_tile.pos
+						+ ScreenVector::new(
+							offset.x * screen_tile.size.x,
+							offset.y * screen_tile.size.y,
+						),
+				)
+				.scale(ScreenVector::new(
+					screen_tile.size.x / cell_size.x,
+					screen_tile.size.y / cell_size.y,
+				))
+				.color(color),
+		);
+	}
+}
+
+impl Renderer for SpriteRenderer<'_> {
+	fn draw_tile(
+		&mut self,
+		coords: TilePoint,
+		tile: Tile,
+		wall_mask: WallMask,
+		perception: Perception,
+	) {
+		let color = match perception {
+			Perception::Seen => Color::WHITE,
+			Perception::Remembered => Color::from_rgba(255, 255, 255, 64),
+		};
+		let source = self.tileset.tile(tile, wall_mask);
+		self.draw_sprite(coords, Pose::STILL.offset, source, color);
+	}
+
+	fn draw_item(&mut self, coords: TilePoint, kind: ItemKind) {
+		let source = self.tileset.item(kind);
+		self.draw_sprite(coords, Pose::STILL.offset, source, Color::WHITE);
+	}
+
+	fn draw_creature(
+		&mut self,
+		coords: TilePoint,
+		species: Species,
+		pose: Pose,
+	) {
+		let source = self.tileset.creature(species);
+		self.draw_sprite(coords, pose.offset, source, pose.color);
+	}
+
+	fn draw_highlight(&mut self, coords: TilePoint) {
+		let screen_tile = self.tile_layout.to_screen(coords);
+		self.canvas.draw(
+			&self.meshes.highlight,
+			DrawParam::new()
+				.dest(screen_tile.pos)
+				.scale(screen_tile.size),
+		);
+	}
+
+	fn finish(self: Box<Self>) {
+		if !self.tileset.instances.instances().is_empty() {
+			self.canvas
+				.draw(&self.tileset.instances, DrawParam::new().z(BATCH_Z));
+		}
+	}
+}
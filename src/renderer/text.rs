@@ -0,0 +1,115 @@
+use crate::{
+	creature::Species,
+	geometry::{TilePoint, TileRectangle},
+	item::ItemKind,
+	level::Tile,
+	renderer::{Perception, Pose, Renderer, WallMask},
+};
+
+/// Draws to a grid of characters, one per tile, using each thing's glyph.
+/// Remembered tiles look the same as seen ones, since text has no color, and
+/// highlights aren't shown, since they would hide what's highlighted.
+pub struct TextRenderer {
+	/// The region of tiles covered by the grid.
+	bounds: TileRectangle,
+	/// The grid's characters in row-major order.
+	glyphs: Vec<char>,
+}
+
+impl TextRenderer {
+	/// A blank grid covering `bounds`.
+	pub fn new(bounds: TileRectangle) -> TextRenderer {
+		TextRenderer {
+			bounds,
+			glyphs: vec![' '; bounds.area() as usize],
+		}
+	}
+
+	/// The grid drawn so far, one line per row of tiles. An empty grid has no
+	/// lines.
+	pub fn frame(&self) -> String {
+		if self.glyphs.is_empty() {
+			return String::new();
+		}
+		self.glyphs
+			.chunks(self.bounds.size.x as usize)
+			.flat_map(|row| row.iter().copied().chain(['\n']))
+			.collect()
+	}
+
+	/// Sets the glyph at `coords`, ignoring coordinates outside the grid.
+	fn put(&mut self, coords: TilePoint, glyph: char) {
+		if self.bounds.contains(coords) {
+			let offset = coords - self.bounds.pos;
+			let i = offset.y * self.bounds.size.x + offset.x;
+			self.glyphs[i as usize] = glyph;
+		}
+	}
+}
+
+impl Renderer for TextRenderer {
+	fn draw_tile(
+		&mut self,
+		coords: TilePoint,
+		tile: Tile,
+		_: WallMask,
+		_: Perception,
+	) {
+		self.put(coords, tile.appearance().glyph);
+	}
+
+	fn draw_item(&mut self, coords: TilePoint, kind: ItemKind) {
+		self.put(coords, kind.appearance().glyph);
+	}
+
+	fn draw_creature(&mut self, coords: TilePoint, species: Species, _: Pose) {
+		self.put(coords, species.appearance().glyph);
+	}
+
+	fn draw_highlight(&mut self, _: TilePoint) {}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		geometry::TileVector,
+		level::{Floor, Tile},
+	};
+
+	#[test]
+	fn draws_the_last_thing_on_each_tile() {
+		let mut renderer = TextRenderer::new(TileRectangle {
+			pos: TilePoint::new(1, 1),
+			size: TileVector::new(3, 2),
+		});
+		let stone = Tile::Floor(Floor::Stone);
+		for coords in [TilePoint::new(1, 1), TilePoint::new(2, 2)] {
+			renderer.draw_tile(coords, stone, 0, Perception::Seen);
+		}
+		renderer.draw_item(TilePoint::new(2, 2), ItemKind::Gold);
+		renderer.draw_creature(
+			TilePoint::new(3, 1),
+			Species::Goblin,
+			Pose::STILL,
+		);
+		// Outside the grid, so ignored.
+		renderer.draw_creature(
+			TilePoint::new(0, 0),
+			Species::Ogre,
+			Pose::STILL,
+		);
+		assert_eq!(renderer.frame(), ". g\n $ \n");
+	}
+
+	#[test]
+	fn empty_bounds_have_no_lines() {
+		for size in [TileVector::new(0, 5), TileVector::new(5, 0)] {
+			let renderer = TextRenderer::new(TileRectangle {
+				pos: TilePoint::new(0, 0),
+				size,
+			});
+			assert_eq!(renderer.frame(), "");
+		}
+	}
+}
@@ -0,0 +1,66 @@
+use std::ops::RangeInclusive;
+
+use rand::{seq::SliceRandom, Rng};
+use rand_pcg::Pcg32;
+
+use crate::{
+	creature::Species,
+	generation::{check_ratio, GenerationError},
+};
+
+/// A kind of creature that may be spawned from a [`SpawnTable`].
+pub struct SpawnEntry {
+	pub species: Species,
+	/// The relative likelihood of choosing this entry over others.
+	pub weight: u32,
+	/// The number of creatures spawned together as a group.
+	pub group_size: RangeInclusive<u32>,
+	/// The level depths at which this entry may be chosen.
+	pub depths: RangeInclusive<u32>,
+}
+
+impl SpawnEntry {
+	/// A random group size for this entry.
+	pub fn roll_group_size(&self, rng: &mut Pcg32) -> u32 {
+		rng.gen_range(self.group_size.clone())
+	}
+}
+
+/// Describes which creatures populate a level and how many.
+pub struct SpawnTable {
+	pub entries: Vec<SpawnEntry>,
+	/// The number of creatures to spawn per open floor tile.
+	pub density: f32,
+	/// Creatures don't spawn in the player's starting room or within this
+	/// walking distance of the player's starting point.
+	pub safe_radius: usize,
+}
+
+impl SpawnTable {
+	/// Checks that spawning from this table won't panic.
+	pub fn validate(&self) -> Result<(), GenerationError> {
+		check_ratio("density", self.density)?;
+		match self
+			.entries
+			.iter()
+			.find(|entry| entry.group_size.is_empty())
+		{
+			Some(entry) => Err(GenerationError::EmptyGroupSize(entry.species)),
+			None => Ok(()),
+		}
+	}
+
+	/// Chooses an entry available at `depth` at random, using the entries'
+	/// weights, or `None` if no entries are available.
+	pub fn choose(&self, depth: u32, rng: &mut Pcg32) -> Option<&SpawnEntry> {
+		let available = self
+			.entries
+			.iter()
+			.filter(|entry| entry.depths.contains(&depth))
+			.collect::<Vec<_>>();
+		available
+			.choose_weighted(rng, |entry| entry.weight)
+			.ok()
+			.copied()
+	}
+}
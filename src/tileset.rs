@@ -0,0 +1,268 @@
+use std::{collections::HashMap, path::Path};
+
+use ggez::{
+	graphics::{Image, InstanceArray, Rect},
+	Context, GameError, GameResult,
+};
+
+use crate::{
+	creature::Species,
+	geometry::ScreenVector,
+	item::ItemKind,
+	level::{Floor, Tile},
+	renderer::WallMask,
+};
+
+/// Sprites for drawing levels, cut from the cells of an atlas image.
+///
+/// A tileset is loaded from a mapping file. Its `image` line gives the atlas
+/// path, relative to the mapping file, and its `cell` line gives the width and
+/// height of each cell in pixels. Every other line maps a sprite name to the
+/// column and row of its cell. Blank lines and lines starting with `#` are
+/// ignored. Every tile, species, and item kind needs a sprite. Walls may also
+/// have a sprite for each [`WallMask`], named `wall_<mask>`, to join them with
+/// neighboring walls.
+pub struct Tileset {
+	/// The width and height of a cell in pixels.
+	pub cell_size: ScreenVector,
+	/// A buffer for drawing many sprites from the atlas image at once.
+	pub instances: InstanceArray,
+	/// Sprite source rectangles, relative to the image's size.
+	tiles: HashMap<Tile, Rect>,
+	walls: HashMap<WallMask, Rect>,
+	species: HashMap<Species, Rect>,
+	items: HashMap<ItemKind, Rect>,
+}
+
+impl Tileset {
+	/// Loads a tileset from the mapping file at `path` and the atlas image it
+	/// names. Fails if the cell size is zero or a cell lies outside the image.
+	pub fn load(ctx: &Context, path: &Path) -> GameResult<Tileset> {
+		let error = |message: String| {
+			GameError::ResourceLoadError(format!(
+				"{}: {message}",
+				path.display()
+			))
+		};
+		let text = std::fs::read_to_string(path)?;
+		let mapping = Mapping::parse(&text).map_err(error)?;
+		let bytes = std::fs::read(
+			path.parent().unwrap_or(Path::new("")).join(mapping.image),
+		)?;
+		let image = Image::from_bytes(ctx, &bytes)?;
+		mapping
+			.check_bounds(image.width(), image.height())
+			.map_err(error)?;
+
+		let (cell_width, cell_height) = mapping.cell_size;
+		let source = |name: &str| {
+			mapping.cells.get(name).map(|&(x, y, _)| Rect {
+				x: (x * cell_width) as f32 / image.width() as f32,
+				y: (y * cell_height) as f32 / image.height() as f32,
+				w: cell_width as f32 / image.width() as f32,
+				h: cell_height as f32 / image.height() as f32,
+			})
+		};
+		let required = |name: &str| {
+			source(name)
+				.ok_or_else(|| error(format!("missing sprite {name:?}")))
+		};
+		let mut tiles = HashMap::new();
+		for tile in Tile::ALL {
+			tiles.insert(tile, required(tile_sprite_name(tile))?);
+		}
+		let mut species = HashMap::new();
+		for s in Species::ALL {
+			species.insert(s, required(species_sprite_name(s))?);
+		}
+		let mut items = HashMap::new();
+		for kind in ItemKind::ALL {
+			items.insert(kind, required(item_sprite_name(kind))?);
+		}
+		let walls = (0..16)
+			.filter_map(|mask| {
+				source(&format!("wall_{mask}")).map(|rect| (mask, rect))
+			})
+			.collect();
+
+		Ok(Tileset {
+			cell_size: ScreenVector::new(cell_width as f32, cell_height as f32),
+			instances: InstanceArray::new(ctx, image),
+			tiles,
+			walls,
+			species,
+			items,
+		})
+	}
+
+	/// The source rectangle of `tile`'s sprite, given which of its neighbors
+	/// are walls.
+	pub fn tile(&self, tile: Tile, wall_mask: WallMask) -> Rect {
+		let wall = match tile {
+			Tile::Wall => self.walls.get(&wall_mask),
+			Tile::Floor(_) => None,
+		};
+		*wall.unwrap_or(&self.tiles[&tile])
+	}
+
+	/// The source rectangle of `species`'s sprite.
+	pub fn creature(&self, species: Species) -> Rect {
+		self.species[&species]
+	}
+
+	/// The source rectangle of `kind`'s sprite.
+	pub fn item(&self, kind: ItemKind) -> Rect {
+		self.items[&kind]
+	}
+}
+
+/// The contents of a tileset's mapping file.
+struct Mapping<'a> {
+	/// The path of the atlas image, relative to the mapping file.
+	image: &'a str,
+	/// The width and height of a cell in pixels.
+	cell_size: (u32, u32),
+	/// The column and row of each sprite's cell, and the line it's on.
+	cells: HashMap<&'a str, (u32, u32, usize)>,
+}
+
+impl<'a> Mapping<'a> {
+	/// Parses a mapping file. Fails with a message naming the offending line,
+	/// if there is one.
+	fn parse(text: &'a str) -> Result<Mapping<'a>, String> {
+		let mut image = None;
+		let mut cell_size = None;
+		let mut cells = HashMap::new();
+		for (i, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let error = |message: &str| format!("line {}: {message}", i + 1);
+			let words = line.split_whitespace().collect::<Vec<_>>();
+			match words[..] {
+				["image", path] => image = Some(path),
+				[name, x, y] => {
+					let x = x
+						.parse::<u32>()
+						.map_err(|_| error("invalid number"))?;
+					let y = y
+						.parse::<u32>()
+						.map_err(|_| error("invalid number"))?;
+					if name != "cell" {
+						cells.insert(name, (x, y, i + 1));
+					} else if x == 0 || y == 0 {
+						return Err(error("cell size must be positive"));
+					} else {
+						cell_size = Some((x, y));
+					}
+				}
+				_ => return Err(error("expected a name and two numbers")),
+			}
+		}
+		Ok(Mapping {
+			image: image.ok_or("missing image")?,
+			cell_size: cell_size.ok_or("missing cell size")?,
+			cells,
+		})
+	}
+
+	/// Checks that every cell lies within a `width` x `height` atlas image.
+	fn check_bounds(&self, width: u32, height: u32) -> Result<(), String> {
+		let mut cells = self.cells.values().collect::<Vec<_>>();
+		cells.sort_by_key(|(_, _, line)| *line);
+		let (cell_width, cell_height) = self.cell_size;
+		for &(x, y, line) in cells {
+			// Widen so that huge cell coordinates can't overflow.
+			let right = (u64::from(x) + 1) * u64::from(cell_width);
+			let bottom = (u64::from(y) + 1) * u64::from(cell_height);
+			if right > u64::from(width) || bottom > u64::from(height) {
+				return Err(format!(
+					"line {line}: cell {x} {y} is outside the {width}x{height} \
+					 image"
+				));
+			}
+		}
+		Ok(())
+	}
+}
+
+fn tile_sprite_name(tile: Tile) -> &'static str {
+	match tile {
+		Tile::Floor(Floor::Stone) => "stone_floor",
+		Tile::Floor(Floor::Grass) => "grass_floor",
+		Tile::Floor(Floor::Wood) => "wood_floor",
+		Tile::Wall => "wall",
+	}
+}
+
+fn species_sprite_name(species: Species) -> &'static str {
+	match species {
+		Species::Human => "human",
+		Species::Goblin => "goblin",
+		Species::Ogre => "ogre",
+	}
+}
+
+fn item_sprite_name(kind: ItemKind) -> &'static str {
+	match kind {
+		ItemKind::Gold => "gold",
+		ItemKind::Potion => "potion",
+		ItemKind::Scroll => "scroll",
+		ItemKind::Weapon => "weapon",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_the_bundled_mapping() {
+		let text = include_str!("../resources/tilesets/simple.txt");
+		let mapping = Mapping::parse(text).unwrap();
+		assert_eq!(mapping.image, "simple.png");
+		assert_eq!(mapping.cell_size, (16, 16));
+		assert_eq!(mapping.cells["stone_floor"].0, 0);
+		assert!(mapping.check_bounds(256, 64).is_ok());
+	}
+
+	#[test]
+	fn reports_the_line_of_each_error() {
+		for (text, expected) in [
+			(
+				"image a.png\ncell 0 16",
+				"line 2: cell size must be positive",
+			),
+			("image a.png\n\ncell 16 x", "line 3: invalid number"),
+			(
+				"# comment\nimage",
+				"line 2: expected a name and two numbers",
+			),
+			("cell 16 16", "missing image"),
+			("image a.png", "missing cell size"),
+		] {
+			assert_eq!(Mapping::parse(text).err().as_deref(), Some(expected));
+		}
+	}
+
+	#[test]
+	fn rejects_cells_outside_the_image() {
+		let text = "image a.png\ncell 16 8\nwall 1 0\ngoblin 2 1\nogre 1 2";
+		let mapping = Mapping::parse(text).unwrap();
+		assert!(mapping.check_bounds(48, 24).is_ok());
+		assert_eq!(
+			mapping.check_bounds(32, 24),
+			Err("line 4: cell 2 1 is outside the 32x24 image".to_string())
+		);
+		assert_eq!(
+			mapping.check_bounds(48, 16),
+			Err("line 5: cell 1 2 is outside the 48x16 image".to_string())
+		);
+
+		// Coordinates too large to multiply by the cell size in 32 bits.
+		let text = "image a.png\ncell 16 16\nwall 4294967295 0";
+		let mapping = Mapping::parse(text).unwrap();
+		assert!(mapping.check_bounds(u32::MAX, 16).is_err());
+	}
+}
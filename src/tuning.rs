@@ -0,0 +1,111 @@
+use std::str::FromStr;
+
+use crate::analysis::LevelRequirements;
+
+/// The adjustable numbers in the game's level generation settings. The
+/// defaults are what the game plays with; the preview tool can override any of
+/// them by name to try out changes.
+pub struct Tuning {
+	/// The room generator's minimum proportion of floor tiles.
+	pub room_floor_ratio: f32,
+	pub min_room_size: i32,
+	pub max_room_size: i32,
+	pub shaped_room_ratio: f32,
+	pub loop_ratio: f32,
+	pub vault_chance: f32,
+	pub fill_ratio: f32,
+	pub smoothing_iterations: u32,
+	/// The cave generator's minimum open region size.
+	pub cave_region_size: usize,
+	pub join_regions: bool,
+	pub pattern_size: usize,
+	/// The wave function collapse generator's minimum open region size.
+	pub wfc_region_size: usize,
+	/// The number of times wave function collapse may restart.
+	pub wfc_attempts: u32,
+	pub spawn_density: f32,
+	pub safe_radius: usize,
+	pub item_budget: usize,
+	pub treasure_bias: u32,
+	pub requirements: LevelRequirements,
+	pub max_attempts: u32,
+}
+
+impl Default for Tuning {
+	fn default() -> Tuning {
+		Tuning {
+			room_floor_ratio: 0.4,
+			min_room_size: 3,
+			max_room_size: 15,
+			shaped_room_ratio: 0.4,
+			loop_ratio: 0.2,
+			vault_chance: 0.5,
+			fill_ratio: 0.45,
+			smoothing_iterations: 4,
+			cave_region_size: 20,
+			join_regions: true,
+			pattern_size: 3,
+			wfc_region_size: 8,
+			wfc_attempts: 10,
+			spawn_density: 0.015,
+			safe_radius: 10,
+			item_budget: 12,
+			treasure_bias: 8,
+			requirements: LevelRequirements {
+				connected: true,
+				min_floor_ratio: 0.2,
+				max_dead_ends: 40,
+				min_longest_path_estimate: 40,
+			},
+			max_attempts: 10,
+		}
+	}
+}
+
+impl Tuning {
+	/// Sets the value called `name` by parsing `value`. Fails if there's no
+	/// such value or if `value` can't be parsed.
+	pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+		match name {
+			"room-floor-ratio" => self.room_floor_ratio = parse(name, value)?,
+			"min-room-size" => self.min_room_size = parse(name, value)?,
+			"max-room-size" => self.max_room_size = parse(name, value)?,
+			"shaped-room-ratio" => self.shaped_room_ratio = parse(name, value)?,
+			"loop-ratio" => self.loop_ratio = parse(name, value)?,
+			"vault-chance" => self.vault_chance = parse(name, value)?,
+			"fill-ratio" => self.fill_ratio = parse(name, value)?,
+			"smoothing-iterations" => {
+				self.smoothing_iterations = parse(name, value)?
+			}
+			"cave-region-size" => self.cave_region_size = parse(name, value)?,
+			"join-regions" => self.join_regions = parse(name, value)?,
+			"pattern-size" => self.pattern_size = parse(name, value)?,
+			"wfc-region-size" => self.wfc_region_size = parse(name, value)?,
+			"wfc-attempts" => self.wfc_attempts = parse(name, value)?,
+			"spawn-density" => self.spawn_density = parse(name, value)?,
+			"safe-radius" => self.safe_radius = parse(name, value)?,
+			"item-budget" => self.item_budget = parse(name, value)?,
+			"treasure-bias" => self.treasure_bias = parse(name, value)?,
+			"connected" => self.requirements.connected = parse(name, value)?,
+			"min-floor-ratio" => {
+				self.requirements.min_floor_ratio = parse(name, value)?
+			}
+			"max-dead-ends" => {
+				self.requirements.max_dead_ends = parse(name, value)?
+			}
+			"min-longest-path" => {
+				self.requirements.min_longest_path_estimate =
+					parse(name, value)?
+			}
+			"max-attempts" => self.max_attempts = parse(name, value)?,
+			_ => return Err(format!("unknown setting {name:?}")),
+		}
+		Ok(())
+	}
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+	value
+		.parse()
+		.map_err(|_| format!("invalid value {value:?} for {name}"))
+}